@@ -1,6 +1,13 @@
+use crate::events::FeeRecipientSet;
+use crate::flash::FlashLoanReceiverClient;
 use crate::storage::*;
 use num_integer::Roots;
-use soroban_sdk::{contract, contractimpl, token, Address, Env};
+use soroban_sdk::{contract, contractimpl, token, Address, Bytes, Env, I256};
+use vault_interface::VaultContractClient;
+use yield_manager_interface::{VaultType, YieldManagerClient};
+
+/// Fixed-point scale of `spot_price`: 1.0 is 10^7
+pub const SPOT_PRICE_SCALE: i128 = 10_000_000;
 
 /// Transfers tokens from the contract to a recipient address
 ///
@@ -33,10 +40,39 @@ fn transfer_b(e: &Env, to: Address, amount: i128) {
     transfer(e, get_token_b(e), to, amount);
 }
 
+/// a * b widened to 256 bits. Amounts of 13-decimal tokens (PT and YT) times
+/// the other reserve, or times 1000 for the fee scaling, don't fit in an i128
+///
+/// # Arguments
+/// * `e` - The environment
+/// * `a` - The first factor
+/// * `b` - The second factor
+fn mul_wide(e: &Env, a: i128, b: i128) -> I256 {
+    I256::from_i128(e, a).mul(&I256::from_i128(e, b))
+}
+
+/// a * b / c with the product taken in 256 bits, rounded toward zero
+///
+/// # Arguments
+/// * `e` - The environment
+/// * `a` - The first factor
+/// * `b` - The second factor
+/// * `c` - The divisor
+///
+/// # Panics
+/// * If the quotient doesn't fit in an i128
+fn mul_div(e: &Env, a: i128, b: i128, c: i128) -> i128 {
+    mul_wide(e, a, b)
+        .div(&I256::from_i128(e, c))
+        .to_i128()
+        .expect("mul_div overflow")
+}
+
 /// Calculates the optimal deposit amounts based on current pool reserves
 /// Maintains the constant product ratio (x * y = k) for balanced deposits
 ///
 /// # Arguments
+/// * `e` - The environment
 /// * `desired_a` - Desired amount of token A to deposit
 /// * `min_a` - Minimum acceptable amount of token A
 /// * `desired_b` - Desired amount of token B to deposit
@@ -47,6 +83,7 @@ fn transfer_b(e: &Env, to: Address, amount: i128) {
 /// # Returns
 /// A tuple (amount_a, amount_b) representing the actual deposit amounts
 fn get_deposit_amounts(
+    e: &Env,
     desired_a: i128,
     min_a: i128,
     desired_b: i128,
@@ -55,17 +92,24 @@ fn get_deposit_amounts(
     reserve_b: i128,
 ) -> (i128, i128) {
     if reserve_a == 0 && reserve_b == 0 {
+        // The first deposit sets the ratio, the minimums still guard against a front-run seed
+        if desired_a < min_a {
+            panic!("amount_a less than min")
+        }
+        if desired_b < min_b {
+            panic!("amount_b less than min")
+        }
         return (desired_a, desired_b);
     }
 
-    let amount_b = desired_a * reserve_b / reserve_a;
+    let amount_b = mul_div(e, desired_a, reserve_b, reserve_a);
     if amount_b <= desired_b {
         if amount_b < min_b {
             panic!("amount_b less than min")
         }
         (desired_a, amount_b)
     } else {
-        let amount_a = desired_b * reserve_a / reserve_b;
+        let amount_a = mul_div(e, desired_b, reserve_a, reserve_b);
         if amount_a > desired_a || amount_a < min_a {
             panic!("amount_a invalid")
         }
@@ -73,6 +117,235 @@ fn get_deposit_amounts(
     }
 }
 
+/// Calculates how much of the other token an exact input buys, after the 0.3% fee
+///
+/// # Arguments
+/// * `e` - The environment
+/// * `amount_in` - The exact amount of tokens being sold
+/// * `reserve_in` - Reserve of the token being sold
+/// * `reserve_out` - Reserve of the token being bought
+///
+/// # Returns
+/// The amount of tokens bought, rounded down
+fn get_amount_out(e: &Env, amount_in: i128, reserve_in: i128, reserve_out: i128) -> i128 {
+    let amount_in_with_fee = amount_in * 997;
+    mul_div(e, amount_in_with_fee, reserve_out, reserve_in * 1000 + amount_in_with_fee)
+}
+
+/// Rescales an amount from one decimal basis to another
+///
+/// # Arguments
+/// * `amount` - The amount expressed with `from_decimals` decimals
+/// * `from_decimals` - The decimals the amount is currently expressed in
+/// * `to_decimals` - The decimals to express the amount in
+///
+/// # Returns
+/// The rescaled amount, rounded down when reducing precision
+fn normalize_amount(amount: i128, from_decimals: u32, to_decimals: u32) -> i128 {
+    if from_decimals <= to_decimals {
+        10i128
+            .checked_pow(to_decimals - from_decimals)
+            .and_then(|factor| amount.checked_mul(factor))
+            .expect("normalized amount overflow")
+    } else {
+        10i128
+            .checked_pow(from_decimals - to_decimals)
+            .map(|factor| amount / factor)
+            .expect("normalized amount overflow")
+    }
+}
+
+/// Burns a user's pool shares and removes their pro-rata part of both reserves,
+/// leaving the tokens in the contract for the caller to deliver
+///
+/// # Arguments
+/// * `e` - The environment
+/// * `from` - The share owner
+/// * `share_amount` - The amount of pool shares to burn
+///
+/// # Returns
+/// A tuple (out_a, out_b) of the token amounts removed from the reserves
+///
+/// # Panics
+/// * If share_amount is not strictly positive
+/// * If the user doesn't have enough shares
+fn remove_liquidity(e: &Env, from: &Address, share_amount: i128) -> (i128, i128) {
+    // A negative amount would pass the balance check and mint shares in burn_shares
+    if share_amount <= 0 {
+        panic!("share_amount must be strictly positive");
+    }
+
+    let current_shares = get_shares(e, from);
+    if current_shares < share_amount {
+        panic!("insufficient shares");
+    }
+
+    // The protocol's shares are minted first so they share in the withdrawal
+    mint_protocol_fee(e);
+
+    let (balance_a, balance_b) = (get_balance_a(e), get_balance_b(e));
+    let total_shares = get_total_shares(e);
+
+    // Calculate withdrawal amounts
+    let out_a = mul_div(e, balance_a, share_amount, total_shares);
+    let out_b = mul_div(e, balance_b, share_amount, total_shares);
+
+    burn_shares(e, from, share_amount);
+    put_reserve_a(e, balance_a - out_a);
+    put_reserve_b(e, balance_b - out_b);
+    update_protocol_k_last(e);
+    checkpoint_k(e);
+
+    (out_a, out_b)
+}
+
+/// Drop in the marginal price of the bought token, in basis points, when `sell_amount`
+/// goes in and `out` comes out. The price moves from reserve_buy / reserve_sell to
+/// (reserve_buy - out) / (reserve_sell + sell_amount)
+///
+/// # Panics
+/// * If the reserve products overflow
+fn price_impact_bps(reserve_sell: i128, reserve_buy: i128, sell_amount: i128, out: i128) -> i128 {
+    let new_sell = reserve_sell + sell_amount;
+    let new_buy = reserve_buy - out;
+
+    let before = reserve_buy.checked_mul(new_sell).expect("impact overflow");
+    let after = new_buy.checked_mul(reserve_sell).expect("impact overflow");
+    (before - after).checked_mul(10_000).expect("impact overflow") / before
+}
+
+/// The protocol's cut of the LP fee as pool shares, Uniswap V2 style, with its recipient.
+/// Fees stay in the reserves and grow sqrt(k), so the growth since the last checkpoint
+/// is the fee earned. The recipient is owed the shares worth 1/fraction of it:
+/// total_shares * (root_k - root_k_last) / ((fraction - 1) * root_k + root_k_last).
+/// None when the fee is off or no checkpoint was taken yet
+///
+/// # Panics
+/// * If the reserve product or the share math overflows
+fn protocol_fee_shares(e: &Env) -> Option<(Address, i128)> {
+    let (recipient, fraction) = get_protocol_fee(e)?;
+
+    let k_last = get_protocol_k_last(e);
+    if k_last == 0 {
+        return None;
+    }
+
+    let root_k = current_k(e).sqrt();
+    let root_k_last = k_last.sqrt();
+    if root_k <= root_k_last {
+        return None;
+    }
+
+    let numerator = get_total_shares(e)
+        .checked_mul(root_k - root_k_last)
+        .expect("protocol fee overflow");
+    let denominator = (fraction as i128 - 1)
+        .checked_mul(root_k)
+        .expect("protocol fee overflow")
+        + root_k_last;
+    Some((recipient, numerator / denominator))
+}
+
+/// Mints the protocol fee shares owed since the last checkpoint, see `protocol_fee_shares`
+///
+/// # Panics
+/// * If the reserve product or the share math overflows
+fn mint_protocol_fee(e: &Env) {
+    if let Some((recipient, shares)) = protocol_fee_shares(e) {
+        if shares > 0 {
+            mint_shares(e, &recipient, shares);
+        }
+    }
+}
+
+/// Checkpoints k for the next protocol fee mint, when the fee is on
+fn update_protocol_k_last(e: &Env) {
+    if get_protocol_fee(e).is_some() {
+        put_protocol_k_last(e, current_k(e));
+    }
+}
+
+/// Mints the protocol fee on the k growth of a trade and checkpoints the new k
+fn accrue_protocol_fee(e: &Env) {
+    mint_protocol_fee(e);
+    update_protocol_k_last(e);
+}
+
+/// Constant product of the current reserves
+///
+/// # Panics
+/// * If the product overflows
+fn current_k(e: &Env) -> i128 {
+    get_reserve_a(e)
+        .checked_mul(get_reserve_b(e))
+        .expect("k overflow")
+}
+
+/// Checks a trade did not shrink k and checkpoints the new k. Fees stay in the
+/// reserves, so only a fee accounting bug can make a trade lower k
+///
+/// # Panics
+/// * If k is lower than at the last checkpoint
+fn check_k_growth(e: &Env) {
+    let k = current_k(e);
+    if k < get_last_k(e) {
+        panic!("k decreased");
+    }
+    put_last_k(e, k);
+}
+
+/// Rejects trading and depositing against a drained pool. `emergency_withdraw` zeroes
+/// a reserve with shares still outstanding so LPs can exit pro-rata: a swap would price
+/// the drained side at nothing, and once both sides are drained a deposit would be
+/// minted shares as the first one while the old shares claim part of it
+///
+/// # Panics
+/// * If a reserve is zero while pool shares are outstanding
+fn require_not_drained(e: &Env) {
+    if get_total_shares(e) > 0 && (get_reserve_a(e) == 0 || get_reserve_b(e) == 0) {
+        panic!("pool is drained");
+    }
+}
+
+/// Checks a swap made while paused in rebalancing mode moves the price of token A,
+/// in token B and scaled by `SPOT_PRICE_SCALE`, toward `target` without crossing it
+///
+/// # Arguments
+/// * `e` - The environment
+/// * `target` - The price the pool is being rebalanced to
+/// * `buy_a` - Whether the swap buys token A, raising its price
+/// * `reserves` - The reserves (a, b) before the swap
+/// * `new_reserves` - The reserves (a, b) after the swap
+///
+/// # Panics
+/// * If the swap moves the price away from the target or past it
+fn require_rebalancing(
+    e: &Env,
+    target: i128,
+    buy_a: bool,
+    reserves: (i128, i128),
+    new_reserves: (i128, i128),
+) {
+    // price_a = reserve_b * scale / reserve_a, compared to the target without dividing
+    let price_vs_target = |(reserve_a, reserve_b): (i128, i128)| {
+        mul_wide(e, reserve_b, SPOT_PRICE_SCALE).cmp(&mul_wide(e, target, reserve_a))
+    };
+
+    let toward = if buy_a {
+        price_vs_target(reserves).is_lt() && price_vs_target(new_reserves).is_le()
+    } else {
+        price_vs_target(reserves).is_gt() && price_vs_target(new_reserves).is_ge()
+    };
+    if !toward {
+        panic!("pool is paused, swap must rebalance toward the target");
+    }
+}
+
+/// Re-baselines the k checkpoint after liquidity legitimately moved k
+fn checkpoint_k(e: &Env) {
+    put_last_k(e, current_k(e));
+}
+
 #[contract]
 pub struct LiquidityPool;
 
@@ -83,18 +356,48 @@ impl LiquidityPool {
     ///
     /// # Arguments
     /// * `e` - The environment
+    /// * `admin` - Can pause the pool and drain a token in an emergency
     /// * `token_a` - The first token contract address (must be < token_b)
     /// * `token_b` - The second token contract address (must be > token_a)
+    /// * `yield_manager` - The yield manager whose PT/YT trades in this pool, if any.
+    ///   Its maturity gates `settle`
+    /// * `protocol_fee_recipient` - Receives the protocol's cut of the LP fee as pool
+    ///   shares. None leaves the whole fee to the LPs
+    /// * `protocol_fee_fraction` - The protocol takes 1/fraction of the LP fee
+    ///   (6 for Uniswap V2's 1/6), applied once a recipient is set
     ///
     /// # Panics
     /// Panics if token_a >= token_b
-    pub fn __constructor(e: Env, token_a: Address, token_b: Address) {
+    /// Panics if a protocol fee recipient is set and the fraction is zero
+    pub fn __constructor(
+        e: Env,
+        admin: Address,
+        token_a: Address,
+        token_b: Address,
+        yield_manager: Option<Address>,
+        protocol_fee_recipient: Option<Address>,
+        protocol_fee_fraction: u32,
+    ) {
         if token_a >= token_b {
             panic!("token_a must be less than token_b");
         }
 
+        if protocol_fee_recipient.is_some() && protocol_fee_fraction == 0 {
+            panic!("protocol fee fraction must be positive");
+        }
+        put_protocol_fee_fraction(&e, protocol_fee_fraction);
+        put_protocol_fee_recipient(&e, protocol_fee_recipient);
+
+        put_admin(&e, admin);
+
+        // Decimals are cached so reserve views don't need a cross-contract call
+        put_decimals_a(&e, token::Client::new(&e, &token_a).decimals());
+        put_decimals_b(&e, token::Client::new(&e, &token_b).decimals());
         put_token_a(&e, token_a);
         put_token_b(&e, token_b);
+        if let Some(yield_manager) = yield_manager {
+            put_yield_manager(&e, yield_manager);
+        }
         put_total_shares(&e, 0);
         put_reserve_a(&e, 0);
         put_reserve_b(&e, 0);
@@ -112,6 +415,78 @@ impl LiquidityPool {
         get_shares(&e, &user)
     }
 
+    /// Returns the total amount of pool shares outstanding
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn total_shares(e: Env) -> i128 {
+        get_total_shares(&e)
+    }
+
+    /// Returns the address of the pool's first token (the lower of the pair)
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn token_a(e: Env) -> Address {
+        get_token_a(&e)
+    }
+
+    /// Returns the address of the pool's second token (the higher of the pair)
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn token_b(e: Env) -> Address {
+        get_token_b(&e)
+    }
+
+    /// Returns everything a front-end needs to render a pool position in one call
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `user` - The user address to query
+    ///
+    /// # Returns
+    /// A tuple (reserve_a, reserve_b, total_shares, user_shares)
+    pub fn pool_info(e: Env, user: Address) -> (i128, i128, i128, i128) {
+        (
+            get_reserve_a(&e),
+            get_reserve_b(&e),
+            get_total_shares(&e),
+            get_shares(&e, &user),
+        )
+    }
+
+    /// Quotes a deposit at the current reserves without mutating state: the amounts
+    /// `deposit` would take for the desired amounts (with no minimums) and the shares
+    /// it would mint. An empty pool accepts any ratio and mints the geometric mean
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `desired_a` - Desired amount of token A to deposit
+    /// * `desired_b` - Desired amount of token B to deposit
+    ///
+    /// # Returns
+    /// A tuple (amount_a, amount_b, shares_minted)
+    pub fn quote_deposit(e: Env, desired_a: i128, desired_b: i128) -> (i128, i128, i128) {
+        let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
+        let (amount_a, amount_b) =
+            get_deposit_amounts(&e, desired_a, 0, desired_b, 0, reserve_a, reserve_b);
+
+        // deposit mints the pending protocol fee first, diluting the new shares
+        let fee_shares = protocol_fee_shares(&e).map_or(0, |(_, shares)| shares);
+        let total_shares = get_total_shares(&e) + fee_shares;
+        let shares = if reserve_a > 0 && reserve_b > 0 {
+            // Same as deposit's (balance * total_shares) / reserve - total_shares
+            let shares_a = mul_div(&e, amount_a, total_shares, reserve_a);
+            let shares_b = mul_div(&e, amount_b, total_shares, reserve_b);
+            shares_a.min(shares_b)
+        } else {
+            (amount_a * amount_b).sqrt()
+        };
+
+        (amount_a, amount_b, shares)
+    }
+
     /// Deposits tokens into the liquidity pool and mints pool shares
     /// The deposit ratio must match the current pool ratio to maintain balance
     /// For the first deposit (empty pool), any ratio is accepted
@@ -127,6 +502,8 @@ impl LiquidityPool {
     /// # Panics
     /// * If calculated amounts are below minimum thresholds
     /// * If either deposit amount would be zero or negative
+    /// * If the pool has been settled
+    /// * If a reserve was drained while shares are outstanding
     pub fn deposit(
         e: Env,
         to: Address,
@@ -137,12 +514,23 @@ impl LiquidityPool {
     ) {
         // Depositor needs to authorize the deposit
         to.require_auth();
+        extend_instance_ttl(&e);
+
+        if is_settled(&e) {
+            panic!("pool is settled");
+        }
+
+        if is_paused(&e) {
+            panic!("pool is paused");
+        }
+
+        require_not_drained(&e);
 
         let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
 
         // Calculate deposit amounts
         let (amount_a, amount_b) =
-            get_deposit_amounts(desired_a, min_a, desired_b, min_b, reserve_a, reserve_b);
+            get_deposit_amounts(&e, desired_a, min_a, desired_b, min_b, reserve_a, reserve_b);
 
         if amount_a <= 0 || amount_b <= 0 {
             // If one of the amounts can be zero, we can get into a situation
@@ -153,8 +541,11 @@ impl LiquidityPool {
         let token_a_client = token::Client::new(&e, &get_token_a(&e));
         let token_b_client = token::Client::new(&e, &get_token_b(&e));
 
-        token_a_client.transfer(&to, &e.current_contract_address(), &amount_a);
-        token_b_client.transfer(&to, &e.current_contract_address(), &amount_b);
+        token_a_client.transfer(&to, e.current_contract_address(), &amount_a);
+        token_b_client.transfer(&to, e.current_contract_address(), &amount_b);
+
+        // Any fee earned since the last checkpoint belongs to the existing shares
+        mint_protocol_fee(&e);
 
         // Now calculate how many new pool shares to mint
         let (balance_a, balance_b) = (get_balance_a(&e), get_balance_b(&e));
@@ -162,8 +553,8 @@ impl LiquidityPool {
 
         let zero = 0;
         let new_total_shares = if reserve_a > zero && reserve_b > zero {
-            let shares_a = (balance_a * total_shares) / reserve_a;
-            let shares_b = (balance_b * total_shares) / reserve_b;
+            let shares_a = mul_div(&e, balance_a, total_shares, reserve_a);
+            let shares_b = mul_div(&e, balance_b, total_shares, reserve_b);
             shares_a.min(shares_b)
         } else {
             (balance_a * balance_b).sqrt()
@@ -172,6 +563,8 @@ impl LiquidityPool {
         mint_shares(&e, &to, new_total_shares - total_shares);
         put_reserve_a(&e, balance_a);
         put_reserve_b(&e, balance_b);
+        update_protocol_k_last(&e);
+        checkpoint_k(&e);
     }
 
     /// Swaps tokens in the liquidity pool using a constant product formula with 0.3% fee
@@ -183,6 +576,8 @@ impl LiquidityPool {
     /// * `buy_a` - If true, buys token A and sells token B; if false, buys token B and sells token A
     /// * `out` - The exact amount of tokens to receive
     /// * `in_max` - Maximum amount of tokens willing to sell (slippage protection)
+    /// * `max_impact_bps` - Optional cap, in basis points, on how far the trade may move
+    ///   the pool's marginal price
     ///
     /// # How it works
     /// 1. Calculates required sell amount based on constant product formula
@@ -192,12 +587,49 @@ impl LiquidityPool {
     /// 5. Updates reserves
     ///
     /// # Panics
+    /// * If out or in_max is not strictly positive
     /// * If there aren't enough tokens in the pool to buy
     /// * If the required sell amount exceeds in_max
+    /// * If the price impact exceeds max_impact_bps
     /// * If the constant product invariant doesn't hold
     /// * If resulting reserves would be zero or negative
-    pub fn swap(e: Env, to: Address, buy_a: bool, out: i128, in_max: i128) {
+    /// * If the pool has been settled
+    /// * If the pool is paused, unless it is in rebalancing mode and the swap moves the
+    ///   price toward the target without crossing it
+    /// * If a reserve was drained while shares are outstanding
+    pub fn swap(
+        e: Env,
+        to: Address,
+        buy_a: bool,
+        out: i128,
+        in_max: i128,
+        max_impact_bps: Option<u32>,
+    ) {
         to.require_auth();
+        extend_instance_ttl(&e);
+
+        if out <= 0 {
+            panic!("out must be strictly positive");
+        }
+        if in_max <= 0 {
+            panic!("in_max must be strictly positive");
+        }
+
+        if is_settled(&e) {
+            panic!("pool is settled");
+        }
+
+        // A rebalancing pause lets arbitrageurs move the price back toward the target
+        let rebalance_target = if is_paused(&e) {
+            match get_rebalance_target(&e) {
+                Some(target) => Some(target),
+                None => panic!("pool is paused"),
+            }
+        } else {
+            None
+        };
+
+        require_not_drained(&e);
 
         let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
         let (reserve_sell, reserve_buy) = if buy_a {
@@ -211,13 +643,28 @@ impl LiquidityPool {
         }
 
         // First calculate how much needs to be sold to buy amount out from the pool
-        let n = reserve_sell * out * 1000;
         let d = (reserve_buy - out) * 997;
-        let sell_amount = (n / d) + 1;
+        let sell_amount = mul_div(&e, reserve_sell, out * 1000, d) + 1;
         if sell_amount > in_max {
             panic!("in amount is over max")
         }
 
+        if let Some(target) = rebalance_target {
+            let new_reserves = if buy_a {
+                (reserve_a - out, reserve_b + sell_amount)
+            } else {
+                (reserve_a + sell_amount, reserve_b - out)
+            };
+            require_rebalancing(&e, target, buy_a, (reserve_a, reserve_b), new_reserves);
+        }
+
+        if let Some(max_impact_bps) = max_impact_bps {
+            let impact = price_impact_bps(reserve_sell, reserve_buy, sell_amount, out);
+            if impact > max_impact_bps as i128 {
+                panic!("price impact too high");
+            }
+        }
+
         // Transfer the amount being sold to the contract
         let sell_token = if buy_a {
             get_token_b(&e)
@@ -225,7 +672,7 @@ impl LiquidityPool {
             get_token_a(&e)
         };
         let sell_token_client = token::Client::new(&e, &sell_token);
-        sell_token_client.transfer(&to, &e.current_contract_address(), &sell_amount);
+        sell_token_client.transfer(&to, e.current_contract_address(), &sell_amount);
 
         let (balance_a, balance_b) = (get_balance_a(&e), get_balance_b(&e));
 
@@ -252,7 +699,7 @@ impl LiquidityPool {
         let old_inv_a = residue_denominator * reserve_a;
         let old_inv_b = residue_denominator * reserve_b;
 
-        if new_inv_a * new_inv_b < old_inv_a * old_inv_b {
+        if mul_wide(&e, new_inv_a, new_inv_b) < mul_wide(&e, old_inv_a, old_inv_b) {
             panic!("constant product invariant does not hold");
         }
 
@@ -271,6 +718,81 @@ impl LiquidityPool {
 
         put_reserve_a(&e, new_reserve_a);
         put_reserve_b(&e, new_reserve_b);
+        accrue_protocol_fee(&e);
+        check_k_growth(&e);
+    }
+
+    /// Lends tokens out of the pool for the duration of a callback. The tokens are
+    /// sent to `to` up front, `to`'s `flash_callback` runs with `data`, and the pool
+    /// then checks against its balances that it was paid back, in either token, enough
+    /// for the constant product to hold after the 0.3% fee on everything paid in
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `to` - The receiver contract (must authorize and implement `FlashLoanReceiver`)
+    /// * `buy_a` - If true, borrows token A; if false, borrows token B
+    /// * `out` - The amount of tokens to borrow
+    /// * `data` - Arbitrary data passed through to the callback
+    ///
+    /// # Panics
+    /// * If the pool has been settled
+    /// * If a reserve was drained while shares are outstanding
+    /// * If `out` isn't positive or there aren't enough tokens in the pool
+    /// * If nothing was paid back
+    /// * If the constant product invariant doesn't hold after the callback
+    pub fn flash_swap(e: Env, to: Address, buy_a: bool, out: i128, data: Bytes) {
+        to.require_auth();
+        extend_instance_ttl(&e);
+
+        if is_settled(&e) {
+            panic!("pool is settled");
+        }
+
+        if is_paused(&e) {
+            panic!("pool is paused");
+        }
+
+        require_not_drained(&e);
+
+        let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
+        let reserve_buy = if buy_a { reserve_a } else { reserve_b };
+        if out <= 0 || out >= reserve_buy {
+            panic!("not enough token to buy");
+        }
+
+        // Optimistic transfer, repayment is checked once the callback returns
+        if buy_a {
+            transfer_a(&e, to.clone(), out);
+        } else {
+            transfer_b(&e, to.clone(), out);
+        }
+        FlashLoanReceiverClient::new(&e, &to).flash_callback(
+            &e.current_contract_address(),
+            &buy_a,
+            &out,
+            &data,
+        );
+
+        let (balance_a, balance_b) = (get_balance_a(&e), get_balance_b(&e));
+        let (out_a, out_b) = if buy_a { (out, 0) } else { (0, out) };
+
+        // Whatever came back on each side, the fee is charged on all of it
+        let in_a = (balance_a - (reserve_a - out_a)).max(0);
+        let in_b = (balance_b - (reserve_b - out_b)).max(0);
+        if in_a == 0 && in_b == 0 {
+            panic!("insufficient input amount");
+        }
+
+        let adjusted_a = balance_a * 1000 - in_a * 3;
+        let adjusted_b = balance_b * 1000 - in_b * 3;
+        if mul_wide(&e, adjusted_a, adjusted_b) < mul_wide(&e, reserve_a * 1000, reserve_b * 1000) {
+            panic!("constant product invariant does not hold");
+        }
+
+        put_reserve_a(&e, balance_a);
+        put_reserve_b(&e, balance_b);
+        accrue_protocol_fee(&e);
+        check_k_growth(&e);
     }
 
     /// Withdraws tokens from the liquidity pool by burning pool shares
@@ -295,6 +817,7 @@ impl LiquidityPool {
     /// 6. Updates reserves
     ///
     /// # Panics
+    /// * If share_amount is not strictly positive
     /// * If user has insufficient shares
     /// * If withdrawal amounts are below minimum thresholds
     pub fn withdraw(
@@ -305,30 +828,447 @@ impl LiquidityPool {
         min_b: i128,
     ) -> (i128, i128) {
         to.require_auth();
+        extend_instance_ttl(&e);
 
-        let current_shares = get_shares(&e, &to);
-        if current_shares < share_amount {
-            panic!("insufficient shares");
+        let (out_a, out_b) = remove_liquidity(&e, &to, share_amount);
+
+        if out_a < min_a || out_b < min_b {
+            panic!("min not satisfied");
         }
 
-        let (balance_a, balance_b) = (get_balance_a(&e), get_balance_b(&e));
-        let total_shares = get_total_shares(&e);
+        transfer_a(&e, to.clone(), out_a);
+        transfer_b(&e, to, out_b);
+
+        (out_a, out_b)
+    }
+
+    /// Withdraws all of the caller's liquidity, burning their entire share balance
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `to` - The address withdrawing tokens (must authorize and own the shares)
+    /// * `min_a` - Minimum acceptable amount of token A to receive
+    /// * `min_b` - Minimum acceptable amount of token B to receive
+    /// * `deadline` - Latest ledger timestamp at which the withdrawal may execute
+    ///
+    /// # Returns
+    /// A tuple (amount_a, amount_b, shares_burned)
+    ///
+    /// # Panics
+    /// * If the deadline has passed
+    /// * If the user has no shares
+    /// * If withdrawal amounts are below minimum thresholds
+    pub fn withdraw_all(
+        e: Env,
+        to: Address,
+        min_a: i128,
+        min_b: i128,
+        deadline: u64,
+    ) -> (i128, i128, i128) {
+        to.require_auth();
+        extend_instance_ttl(&e);
+
+        if e.ledger().timestamp() > deadline {
+            panic!("deadline passed");
+        }
+
+        let share_amount = get_shares(&e, &to);
+        if share_amount == 0 {
+            panic!("insufficient shares");
+        }
 
-        // Calculate withdrawal amounts
-        let out_a = (balance_a * share_amount) / total_shares;
-        let out_b = (balance_b * share_amount) / total_shares;
+        let (out_a, out_b) = remove_liquidity(&e, &to, share_amount);
 
         if out_a < min_a || out_b < min_b {
             panic!("min not satisfied");
         }
 
-        burn_shares(&e, &to, share_amount);
         transfer_a(&e, to.clone(), out_a);
         transfer_b(&e, to, out_b);
-        put_reserve_a(&e, balance_a - out_a);
-        put_reserve_b(&e, balance_b - out_b);
 
-        (out_a, out_b)
+        (out_a, out_b, share_amount)
+    }
+
+    /// Withdraws liquidity entirely into one token. The proportional amount of the
+    /// other token is swapped into the desired one against the pool's reserves
+    /// after the withdrawal, paying the usual 0.3% fee
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `to` - The address withdrawing tokens (must authorize and own the shares)
+    /// * `share_amount` - The number of pool shares to burn
+    /// * `buy_a` - If true, exits into token A; if false, exits into token B
+    /// * `min_out` - Minimum acceptable amount of the desired token
+    /// * `deadline` - Latest ledger timestamp at which the withdrawal may execute
+    ///
+    /// # Returns
+    /// The amount of the desired token sent to `to`
+    ///
+    /// # Panics
+    /// * If the deadline has passed
+    /// * If the pool has been settled
+    /// * If a reserve was drained while shares are outstanding
+    /// * If share_amount is not strictly positive
+    /// * If user has insufficient shares
+    /// * If the withdrawal empties the pool, leaving nothing to swap against
+    /// * If the output is below `min_out`
+    pub fn withdraw_single(
+        e: Env,
+        to: Address,
+        share_amount: i128,
+        buy_a: bool,
+        min_out: i128,
+        deadline: u64,
+    ) -> i128 {
+        to.require_auth();
+        extend_instance_ttl(&e);
+
+        if e.ledger().timestamp() > deadline {
+            panic!("deadline passed");
+        }
+
+        if is_settled(&e) {
+            panic!("pool is settled");
+        }
+
+        if is_paused(&e) {
+            panic!("pool is paused");
+        }
+
+        require_not_drained(&e);
+
+        let (out_a, out_b) = remove_liquidity(&e, &to, share_amount);
+        let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
+
+        // The last LP out would swap against empty reserves, getting nothing for the
+        // other side and leaving it behind as a reserve with no shares
+        if reserve_a <= 0 || reserve_b <= 0 {
+            panic!("pool emptied, use withdraw");
+        }
+
+        // Sell the unwanted side back into the post-withdrawal reserves
+        let (out, new_reserve_a, new_reserve_b) = if buy_a {
+            let swapped = get_amount_out(&e, out_b, reserve_b, reserve_a);
+            (out_a + swapped, reserve_a - swapped, reserve_b + out_b)
+        } else {
+            let swapped = get_amount_out(&e, out_a, reserve_a, reserve_b);
+            (out_b + swapped, reserve_a + out_a, reserve_b - swapped)
+        };
+
+        if out < min_out {
+            panic!("min not satisfied");
+        }
+
+        if new_reserve_a <= 0 || new_reserve_b <= 0 {
+            panic!("new reserves must be strictly positive");
+        }
+
+        put_reserve_a(&e, new_reserve_a);
+        put_reserve_b(&e, new_reserve_b);
+        accrue_protocol_fee(&e);
+        check_k_growth(&e);
+
+        if buy_a {
+            transfer_a(&e, to, out);
+        } else {
+            transfer_b(&e, to, out);
+        }
+
+        out
+    }
+
+    /// Exits a PT/vault-share pool after maturity straight into the underlying asset.
+    /// Withdraws both legs, redeems the PT leg through the yield manager, then
+    /// redeems all vault shares through the vault with the user as receiver
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `user` - The liquidity provider exiting (must authorize)
+    /// * `share_amount` - The amount of pool shares to exit
+    /// * `min_underlying_out` - Minimum acceptable amount of underlying to receive
+    ///
+    /// # Returns
+    /// The amount of underlying delivered to the user. PT dust worth less than one
+    /// vault share can't be redeemed, so it is sent back to the user as PT. A full
+    /// exit never leaves dust since the pool's PT all came from whole shares
+    ///
+    /// # Panics
+    /// * If the pool was created without a yield manager
+    /// * If the pool isn't the yield manager's PT paired with its vault share
+    /// * If the yield manager doesn't wrap a 4626 vault
+    /// * If the yield manager's maturity has not been reached
+    /// * If share_amount is not strictly positive
+    /// * If the user doesn't have enough shares
+    /// * If the underlying delivered is below `min_underlying_out`
+    pub fn exit_matured_pt_pool(
+        e: Env,
+        user: Address,
+        share_amount: i128,
+        min_underlying_out: i128,
+    ) -> i128 {
+        user.require_auth();
+        extend_instance_ttl(&e);
+
+        let yield_manager = match get_yield_manager(&e) {
+            Some(yield_manager) => yield_manager,
+            None => panic!("pool has no yield manager"),
+        };
+        let yield_manager_client = YieldManagerClient::new(&e, &yield_manager);
+
+        if yield_manager_client.get_vault_type() != VaultType::Vault4626 {
+            panic!("vault type not supported");
+        }
+
+        let pt = yield_manager_client.get_principal_token();
+        let vault = yield_manager_client.get_vault();
+        let (token_a, token_b) = (get_token_a(&e), get_token_b(&e));
+        let pt_is_a = if token_a == pt && token_b == vault {
+            true
+        } else if token_b == pt && token_a == vault {
+            false
+        } else {
+            panic!("not a PT/vault share pool");
+        };
+
+        let (out_a, out_b) = remove_liquidity(&e, &user, share_amount);
+        let (pt_out, mut shares_out) = if pt_is_a { (out_a, out_b) } else { (out_b, out_a) };
+
+        // Redeem the PT leg into vault shares held by the pool
+        if pt_out > 0 {
+            let pt_client = token::Client::new(&e, &pt);
+            let pt_before = pt_client.balance(&e.current_contract_address());
+            // Neither leg is bounded on its own, min_underlying_out covers the total
+            shares_out += yield_manager_client.redeem_principal(&e.current_contract_address(), &pt_out, &0);
+
+            let pt_dust = pt_out - (pt_before - pt_client.balance(&e.current_contract_address()));
+            if pt_dust > 0 {
+                transfer(&e, pt, user.clone(), pt_dust);
+            }
+        }
+
+        let underlying_out = if shares_out > 0 {
+            let pool = e.current_contract_address();
+            VaultContractClient::new(&e, &vault).redeem(&shares_out, &user, &pool, &pool)
+        } else {
+            0
+        };
+
+        if underlying_out < min_underlying_out {
+            panic!("min not satisfied");
+        }
+
+        underlying_out
+    }
+
+    /// Pauses the pool: deposits and swaps revert, withdrawals stay open
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    ///
+    /// # Panics
+    /// * If not called by the admin
+    pub fn pause(e: Env) {
+        get_admin(&e).require_auth();
+        put_paused(&e, true);
+        put_rebalance_target(&e, None);
+    }
+
+    /// Pauses the pool but keeps `swap` open for trades that move the price toward
+    /// `target_price`, so arbitrageurs can rebalance a skewed pool during the halt.
+    /// A swap may not cross the target. Everything else behaves as under `pause`
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `target_price` - The price of token A in token B, scaled by `SPOT_PRICE_SCALE`
+    ///   as `spot_price(false)` reports it
+    ///
+    /// # Panics
+    /// * If not called by the admin
+    /// * If the target price is not strictly positive
+    pub fn pause_rebalancing(e: Env, target_price: i128) {
+        get_admin(&e).require_auth();
+        if target_price <= 0 {
+            panic!("target price must be strictly positive");
+        }
+        put_paused(&e, true);
+        put_rebalance_target(&e, Some(target_price));
+    }
+
+    /// Unpauses the pool
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    ///
+    /// # Panics
+    /// * If not called by the admin
+    pub fn unpause(e: Env) {
+        get_admin(&e).require_auth();
+        put_paused(&e, false);
+        put_rebalance_target(&e, None);
+    }
+
+    /// Returns whether the pool is paused
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn is_paused(e: Env) -> bool {
+        is_paused(&e)
+    }
+
+    /// Returns the price swaps may move the pool toward while paused, None when the
+    /// pool isn't paused in rebalancing mode
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn get_rebalance_target(e: Env) -> Option<i128> {
+        get_rebalance_target(&e)
+    }
+
+    /// Changes who receives the protocol's cut of the LP fee. The fee owed so far
+    /// is minted to the previous recipient first, the new one earns from here on
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `recipient` - The new recipient, None turns the protocol fee off
+    ///
+    /// # Panics
+    /// * If not called by the admin
+    /// * If a recipient is set and the pool was created with a zero fee fraction
+    pub fn set_fee_recipient(e: Env, recipient: Option<Address>) {
+        get_admin(&e).require_auth();
+        extend_instance_ttl(&e);
+
+        mint_protocol_fee(&e);
+        put_protocol_fee_recipient(&e, recipient.clone());
+        if let Some((_, 0)) = get_protocol_fee(&e) {
+            panic!("protocol fee fraction must be positive");
+        }
+        // Growth while the fee was off isn't charged: k is checkpointed when it turns on
+        update_protocol_k_last(&e);
+
+        FeeRecipientSet { recipient }.publish(&e);
+    }
+
+    /// Returns the protocol fee recipient, None while the protocol fee is off
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn get_fee_recipient(e: Env) -> Option<Address> {
+        get_protocol_fee_recipient(&e)
+    }
+
+    /// Break-glass recovery for a malicious or frozen token. Sends the pool's whole
+    /// balance of `token` to `to` and zeroes its reserve, so the other token can still
+    /// be withdrawn pro-rata by LPs. Deposits and trades stay closed, even once unpaused,
+    /// until every LP has exited and the pool is empty again
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `to` - The recovery address
+    /// * `token` - The pool token to drain, token A or token B
+    ///
+    /// # Returns
+    /// The amount drained
+    ///
+    /// # Panics
+    /// * If not called by the admin
+    /// * If the pool is not paused
+    /// * If `token` is not one of the pool's tokens
+    pub fn emergency_withdraw(e: Env, to: Address, token: Address) -> i128 {
+        get_admin(&e).require_auth();
+        extend_instance_ttl(&e);
+
+        if !is_paused(&e) {
+            panic!("pool is not paused");
+        }
+
+        let amount = get_balance(&e, token.clone());
+        if token == get_token_a(&e) {
+            put_reserve_a(&e, 0);
+        } else if token == get_token_b(&e) {
+            put_reserve_b(&e, 0);
+        } else {
+            panic!("unknown token");
+        }
+        checkpoint_k(&e);
+
+        if amount > 0 {
+            transfer(&e, token, to, amount);
+        }
+
+        amount
+    }
+
+    /// Settles the pool once the associated yield manager has matured
+    /// After maturity PT redeems for a fixed amount of vault shares, so trading against it
+    /// is pointless. Settling stops swaps and deposits, leaving `withdraw` as a plain
+    /// pro-rata redemption of both reserves for LPs
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    ///
+    /// # Panics
+    /// * If the pool was created without a yield manager
+    /// * If the yield manager's maturity has not been reached
+    /// * If the pool is already settled
+    pub fn settle(e: Env) {
+        let yield_manager = match get_yield_manager(&e) {
+            Some(yield_manager) => yield_manager,
+            None => panic!("pool has no yield manager"),
+        };
+
+        if is_settled(&e) {
+            panic!("pool already settled");
+        }
+
+        let maturity = YieldManagerClient::new(&e, &yield_manager).get_maturity();
+        if e.ledger().timestamp() < maturity {
+            panic!("maturity not reached");
+        }
+
+        put_settled(&e);
+    }
+
+    /// Returns whether the pool has been settled after maturity
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn is_settled(e: Env) -> bool {
+        is_settled(&e)
+    }
+
+    /// Forces the reserves to match the pool's token balances, recovering any
+    /// tokens sent to the pool directly into the reserves (as Uniswap V2's `sync`)
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    pub fn sync(e: Env) {
+        put_reserve_a(&e, get_balance_a(&e));
+        put_reserve_b(&e, get_balance_b(&e));
+        checkpoint_k(&e);
+    }
+
+    /// Sends any token balance held above the reserves to a recipient,
+    /// leaving the reserves untouched (as Uniswap V2's `skim`)
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `to` - The address receiving the surplus
+    ///
+    /// # Returns
+    /// A tuple (excess_a, excess_b) of the amounts sent
+    pub fn skim(e: Env, to: Address) -> (i128, i128) {
+        let excess_a = get_balance_a(&e) - get_reserve_a(&e);
+        let excess_b = get_balance_b(&e) - get_reserve_b(&e);
+
+        if excess_a > 0 {
+            transfer_a(&e, to.clone(), excess_a);
+        }
+        if excess_b > 0 {
+            transfer_b(&e, to, excess_b);
+        }
+
+        (excess_a.max(0), excess_b.max(0))
     }
 
     /// Returns the current reserves of both tokens in the liquidity pool
@@ -341,4 +1281,85 @@ impl LiquidityPool {
     pub fn get_rsrvs(e: Env) -> (i128, i128) {
         (get_reserve_a(&e), get_reserve_b(&e))
     }
+
+    /// Returns the instantaneous marginal price of the pool, ignoring the fee: how much
+    /// of the bought token one unit of the sold token is worth, scaled by
+    /// `SPOT_PRICE_SCALE` (1e7)
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `buy_a` - If true, prices buying token A with token B; if false, buying B with A
+    ///
+    /// # Returns
+    /// reserve_buy * SPOT_PRICE_SCALE / reserve_sell
+    ///
+    /// # Panics
+    /// * If the pool is empty
+    pub fn spot_price(e: Env, buy_a: bool) -> i128 {
+        let (reserve_a, reserve_b) = (get_reserve_a(&e), get_reserve_b(&e));
+        if reserve_a == 0 || reserve_b == 0 {
+            panic!("pool is empty");
+        }
+
+        let (reserve_sell, reserve_buy) = if buy_a {
+            (reserve_b, reserve_a)
+        } else {
+            (reserve_a, reserve_b)
+        };
+
+        reserve_buy
+            .checked_mul(SPOT_PRICE_SCALE)
+            .expect("price overflow")
+            / reserve_sell
+    }
+
+    /// Returns the constant product invariant k = reserve_a * reserve_b
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    ///
+    /// # Panics
+    /// * If the product overflows
+    pub fn get_k(e: Env) -> i128 {
+        current_k(&e)
+    }
+
+    /// Checks k has not decreased since the last checkpoint. Trades check and
+    /// checkpoint k themselves (fees stay in the pool, so a drop points at a fee
+    /// accounting bug), deposits and withdrawals re-baseline it. Read-only
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    ///
+    /// # Returns
+    /// The current k
+    ///
+    /// # Panics
+    /// * If k is lower than at the last checkpoint
+    pub fn assert_k_nondecreasing(e: Env) -> i128 {
+        let k = current_k(&e);
+        if k < get_last_k(&e) {
+            panic!("k decreased");
+        }
+        k
+    }
+
+    /// Returns both reserves rescaled to a common decimal basis, so pools of
+    /// tokens with different decimals can be compared directly
+    ///
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `target_decimals` - The decimals to express both reserves in
+    ///
+    /// # Returns
+    /// A tuple (reserve_a, reserve_b) expressed with `target_decimals` decimals
+    ///
+    /// # Panics
+    /// * If a rescaled reserve overflows
+    pub fn get_reserves_normalized(e: Env, target_decimals: u32) -> (i128, i128) {
+        (
+            normalize_amount(get_reserve_a(&e), get_decimals_a(&e), target_decimals),
+            normalize_amount(get_reserve_b(&e), get_decimals_b(&e), target_decimals),
+        )
+    }
 }
@@ -0,0 +1,7 @@
+use soroban_sdk::{contractevent, Address};
+
+// Published when the admin changes the protocol fee recipient, None turns the fee off
+#[contractevent(topics = ["fee_recipient"], data_format = "single-value")]
+pub struct FeeRecipientSet {
+    pub recipient: Option<Address>,
+}
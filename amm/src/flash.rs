@@ -0,0 +1,15 @@
+use soroban_sdk::{contractclient, Address, Bytes, Env};
+
+/// Interface a contract implements to receive tokens from `flash_swap`.
+/// The pool calls it after sending the borrowed tokens; the receiver must pay
+/// the pool back (in either token, plus the 0.3% fee) before returning
+#[contractclient(name = "FlashLoanReceiverClient")]
+pub trait FlashLoanReceiver {
+    /// # Arguments
+    /// * `e` - The environment
+    /// * `pool` - The pool that sent the tokens and expects repayment
+    /// * `buy_a` - If true, token A was sent; if false, token B was sent
+    /// * `amount` - The amount of tokens sent
+    /// * `data` - Arbitrary data passed through from the `flash_swap` caller
+    fn flash_callback(e: Env, pool: Address, buy_a: bool, amount: i128, data: Bytes);
+}
@@ -1,9 +1,10 @@
 #![no_std]
 
 mod contract;
+mod events;
+mod flash;
 mod storage;
 mod test;
 
-pub use contract::LiquidityPool;
-
-use soroban_sdk::contractmeta;
+pub use contract::{LiquidityPool, SPOT_PRICE_SCALE};
+pub use flash::{FlashLoanReceiver, FlashLoanReceiverClient};
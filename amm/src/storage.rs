@@ -1,14 +1,42 @@
 use soroban_sdk::{contracttype, token, Address, Env};
+use soroban_token_sdk::events::{Burn, Mint};
+
+// Storage TTL constants
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const SHARES_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const SHARES_LIFETIME_THRESHOLD: u32 = SHARES_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
+    Admin,
+    Paused,
     TokenA,
     TokenB,
+    DecimalsA,
+    DecimalsB,
     TotalShares,
     ReserveA,
     ReserveB,
     Shares(Address),
+    YieldManager,
+    Settled,
+    LastK,
+    ProtocolFeeRecipient,
+    ProtocolFeeFraction,
+    ProtocolKLast,
+    RebalanceTarget,
+}
+
+pub fn get_admin(e: &Env) -> Address {
+    e.storage().instance().get(&DataKey::Admin).unwrap()
+}
+
+pub fn is_paused(e: &Env) -> bool {
+    e.storage().instance().get(&DataKey::Paused).unwrap_or(false)
 }
 
 pub fn get_token_a(e: &Env) -> Address {
@@ -19,6 +47,14 @@ pub fn get_token_b(e: &Env) -> Address {
     e.storage().instance().get(&DataKey::TokenB).unwrap()
 }
 
+pub fn get_decimals_a(e: &Env) -> u32 {
+    e.storage().instance().get(&DataKey::DecimalsA).unwrap()
+}
+
+pub fn get_decimals_b(e: &Env) -> u32 {
+    e.storage().instance().get(&DataKey::DecimalsB).unwrap()
+}
+
 pub fn get_total_shares(e: &Env) -> i128 {
     e.storage().instance().get(&DataKey::TotalShares).unwrap()
 }
@@ -31,6 +67,42 @@ pub fn get_reserve_b(e: &Env) -> i128 {
     e.storage().instance().get(&DataKey::ReserveB).unwrap()
 }
 
+pub fn get_last_k(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::LastK).unwrap_or(0)
+}
+
+pub fn get_yield_manager(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&DataKey::YieldManager)
+}
+
+pub fn get_protocol_fee_recipient(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&DataKey::ProtocolFeeRecipient)
+}
+
+/// The protocol fee recipient and fraction, None while the fee is off
+pub fn get_protocol_fee(e: &Env) -> Option<(Address, u32)> {
+    let recipient = get_protocol_fee_recipient(e)?;
+    let fraction = e
+        .storage()
+        .instance()
+        .get(&DataKey::ProtocolFeeFraction)
+        .unwrap_or(0);
+    Some((recipient, fraction))
+}
+
+pub fn get_protocol_k_last(e: &Env) -> i128 {
+    e.storage().instance().get(&DataKey::ProtocolKLast).unwrap_or(0)
+}
+
+/// The price swaps may move the pool toward while it is paused, None under a full pause
+pub fn get_rebalance_target(e: &Env) -> Option<i128> {
+    e.storage().instance().get(&DataKey::RebalanceTarget)
+}
+
+pub fn is_settled(e: &Env) -> bool {
+    e.storage().instance().get(&DataKey::Settled).unwrap_or(false)
+}
+
 pub fn get_balance(e: &Env, contract: Address) -> i128 {
     token::Client::new(e, &contract).balance(&e.current_contract_address())
 }
@@ -44,16 +116,37 @@ pub fn get_balance_b(e: &Env) -> i128 {
 }
 
 pub fn get_shares(e: &Env, user: &Address) -> i128 {
-    e.storage()
-        .persistent()
-        .get(&DataKey::Shares(user.clone()))
-        .unwrap_or(0)
+    let key = DataKey::Shares(user.clone());
+    if let Some(shares) = e.storage().persistent().get::<DataKey, i128>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, SHARES_LIFETIME_THRESHOLD, SHARES_BUMP_AMOUNT);
+        shares
+    } else {
+        0
+    }
 }
 
 pub fn put_shares(e: &Env, user: &Address, amount: i128) {
+    let key = DataKey::Shares(user.clone());
+    e.storage().persistent().set(&key, &amount);
     e.storage()
         .persistent()
-        .set(&DataKey::Shares(user.clone()), &amount);
+        .extend_ttl(&key, SHARES_LIFETIME_THRESHOLD, SHARES_BUMP_AMOUNT);
+}
+
+pub fn extend_instance_ttl(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+pub fn put_admin(e: &Env, admin: Address) {
+    e.storage().instance().set(&DataKey::Admin, &admin);
+}
+
+pub fn put_paused(e: &Env, paused: bool) {
+    e.storage().instance().set(&DataKey::Paused, &paused);
 }
 
 pub fn put_token_a(e: &Env, contract: Address) {
@@ -64,6 +157,53 @@ pub fn put_token_b(e: &Env, contract: Address) {
     e.storage().instance().set(&DataKey::TokenB, &contract);
 }
 
+pub fn put_decimals_a(e: &Env, decimals: u32) {
+    e.storage().instance().set(&DataKey::DecimalsA, &decimals);
+}
+
+pub fn put_decimals_b(e: &Env, decimals: u32) {
+    e.storage().instance().set(&DataKey::DecimalsB, &decimals);
+}
+
+pub fn put_yield_manager(e: &Env, contract: Address) {
+    e.storage().instance().set(&DataKey::YieldManager, &contract);
+}
+
+pub fn put_last_k(e: &Env, k: i128) {
+    e.storage().instance().set(&DataKey::LastK, &k);
+}
+
+pub fn put_protocol_fee_recipient(e: &Env, recipient: Option<Address>) {
+    match recipient {
+        Some(recipient) => e
+            .storage()
+            .instance()
+            .set(&DataKey::ProtocolFeeRecipient, &recipient),
+        None => e.storage().instance().remove(&DataKey::ProtocolFeeRecipient),
+    }
+}
+
+pub fn put_protocol_fee_fraction(e: &Env, fraction: u32) {
+    e.storage()
+        .instance()
+        .set(&DataKey::ProtocolFeeFraction, &fraction);
+}
+
+pub fn put_protocol_k_last(e: &Env, k: i128) {
+    e.storage().instance().set(&DataKey::ProtocolKLast, &k);
+}
+
+pub fn put_rebalance_target(e: &Env, target: Option<i128>) {
+    match target {
+        Some(target) => e.storage().instance().set(&DataKey::RebalanceTarget, &target),
+        None => e.storage().instance().remove(&DataKey::RebalanceTarget),
+    }
+}
+
+pub fn put_settled(e: &Env) {
+    e.storage().instance().set(&DataKey::Settled, &true);
+}
+
 pub fn put_total_shares(e: &Env, amount: i128) {
     e.storage().instance().set(&DataKey::TotalShares, &amount)
 }
@@ -84,6 +224,13 @@ pub fn burn_shares(e: &Env, from: &Address, amount: i128) {
     let total = get_total_shares(e);
     put_shares(e, from, current_shares - amount);
     put_total_shares(e, total - amount);
+
+    // SEP-41 style so wallets can track LP share balances
+    Burn {
+        from: from.clone(),
+        amount,
+    }
+    .publish(e);
 }
 
 pub fn mint_shares(e: &Env, to: &Address, amount: i128) {
@@ -91,4 +238,11 @@ pub fn mint_shares(e: &Env, to: &Address, amount: i128) {
     let total = get_total_shares(e);
     put_shares(e, to, current_shares + amount);
     put_total_shares(e, total + amount);
+
+    Mint {
+        to: to.clone(),
+        to_muxed_id: None,
+        amount,
+    }
+    .publish(e);
 }
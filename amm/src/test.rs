@@ -1,27 +1,103 @@
 #![cfg(test)]
 
-use crate::LiquidityPool;
+use crate::events::FeeRecipientSet;
+use crate::storage::{DataKey, DAY_IN_LEDGERS, INSTANCE_LIFETIME_THRESHOLD};
+use crate::{LiquidityPool, SPOT_PRICE_SCALE};
+use num_integer::Roots;
+use principal_token::PrincipalToken;
 use soroban_sdk::{
-    testutils::Address as _,
-    token::{StellarAssetClient, TokenClient},
-    Address, Env,
+    contract, contractimpl,
+    events::Event,
+    symbol_short,
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events, Ledger, MockAuth, MockAuthInvoke,
+    },
+    token::{self, StellarAssetClient, TokenClient},
+    vec, Address, Bytes, Env, IntoVal, String, Symbol, I256,
 };
+use soroban_token_sdk::events::{Burn, Mint};
+use yield_manager::YieldManager;
+use yield_manager_interface::VaultType;
+use yield_token::YieldToken;
+
+// Stand-in for the yield manager, only exposes the maturity the pool settles against
+#[contract]
+pub struct MockYieldManager;
+
+#[contractimpl]
+impl MockYieldManager {
+    pub fn __constructor(e: Env, maturity: u64) {
+        e.storage().instance().set(&symbol_short!("maturity"), &maturity);
+    }
+
+    pub fn get_maturity(e: Env) -> u64 {
+        e.storage().instance().get(&symbol_short!("maturity")).unwrap()
+    }
+}
+
+// Minimal token with configurable decimals, the Stellar asset contract is always 7
+#[contract]
+pub struct MockToken;
+
+#[contractimpl]
+impl MockToken {
+    pub fn __constructor(e: Env, decimals: u32) {
+        e.storage().instance().set(&symbol_short!("decimals"), &decimals);
+    }
+
+    pub fn decimals(e: Env) -> u32 {
+        e.storage().instance().get(&symbol_short!("decimals")).unwrap()
+    }
+
+    pub fn mint(e: Env, to: Address, amount: i128) {
+        let balance = Self::balance(e.clone(), to.clone());
+        e.storage().persistent().set(&to, &(balance + amount));
+    }
+
+    pub fn balance(e: Env, id: Address) -> i128 {
+        e.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let from_balance = Self::balance(e.clone(), from.clone());
+        if from_balance < amount {
+            panic!("insufficient balance");
+        }
+        e.storage().persistent().set(&from, &(from_balance - amount));
+        Self::mint(e, to, amount);
+    }
+}
 
 struct LiquidityPoolTest<'a> {
     env: Env,
     token_a: TokenClient<'a>,
     token_b: TokenClient<'a>,
     pool: crate::contract::LiquidityPoolClient<'a>,
+    admin: Address,
     user: Address,
+    protocol_fee_recipient: Address,
 }
 
 impl<'a> LiquidityPoolTest<'a> {
     fn setup() -> Self {
+        Self::setup_with_maturity(None)
+    }
+
+    // Optionally links the pool to a mock yield manager maturing at `maturity`
+    fn setup_with_maturity(maturity: Option<u64>) -> Self {
+        Self::setup_with(maturity, None)
+    }
+
+    // Optionally turns the protocol fee on, paid to `protocol_fee_recipient`
+    fn setup_with(maturity: Option<u64>, protocol_fee_fraction: Option<u32>) -> Self {
         let env = Env::default();
         env.mock_all_auths();
 
         let admin = Address::generate(&env);
         let user = Address::generate(&env);
+        let protocol_fee_recipient = Address::generate(&env);
 
         // Create token contracts using Soroban's test token
         let token_a_address = env.register_stellar_asset_contract_v2(admin.clone());
@@ -37,10 +113,19 @@ impl<'a> LiquidityPoolTest<'a> {
             (token_b, token_a)
         };
 
+        let yield_manager = maturity.map(|maturity| env.register(MockYieldManager, (maturity,)));
+
         // Deploy and initialize AMM with constructor arguments
         let pool_contract_id = env.register(
             LiquidityPool,
-            (&token_a_final.address, &token_b_final.address),
+            (
+                &admin,
+                &token_a_final.address,
+                &token_b_final.address,
+                yield_manager,
+                protocol_fee_fraction.map(|_| protocol_fee_recipient.clone()),
+                protocol_fee_fraction.unwrap_or(0),
+            ),
         );
         let pool = crate::contract::LiquidityPoolClient::new(&env, &pool_contract_id);
 
@@ -49,7 +134,9 @@ impl<'a> LiquidityPoolTest<'a> {
             token_a: token_a_final,
             token_b: token_b_final,
             pool,
+            admin,
             user,
+            protocol_fee_recipient,
         }
     }
 
@@ -61,6 +148,34 @@ impl<'a> LiquidityPoolTest<'a> {
         token_a_admin.mint(to, &amount);
         token_b_admin.mint(to, &amount);
     }
+
+    // Asserts the last invocation published `expected` from the pool
+    fn assert_pool_event(&self, expected: &impl Event) {
+        let published = self
+            .env
+            .events()
+            .all()
+            .iter()
+            .filter(|(contract, _, _)| *contract == self.pool.address)
+            .any(|event| {
+                vec![&self.env, event]
+                    == vec![
+                        &self.env,
+                        (
+                            self.pool.address.clone(),
+                            expected.topics(&self.env),
+                            expected.data(&self.env),
+                        ),
+                    ]
+            });
+        assert!(published, "pool event not published");
+    }
+
+    fn advance_ledgers(&self, ledgers: u32) {
+        self.env.ledger().with_mut(|li| {
+            li.sequence_number += ledgers;
+        });
+    }
 }
 
 #[test]
@@ -72,6 +187,15 @@ fn test_initialization() {
     assert_eq!(reserve_b, 0);
 }
 
+#[test]
+fn test_token_pair_getters() {
+    let test = LiquidityPoolTest::setup();
+
+    assert_eq!(test.pool.token_a(), test.token_a.address);
+    assert_eq!(test.pool.token_b(), test.token_b.address);
+    assert!(test.pool.token_a() < test.pool.token_b());
+}
+
 #[test]
 #[should_panic(expected = "token_a must be less than token_b")]
 fn test_initialization_wrong_order() {
@@ -85,12 +209,26 @@ fn test_initialization_wrong_order() {
     if token_a_address.address() > token_b_address.address() {
         let _ = env.register(
             LiquidityPool,
-            (&token_a_address.address(), &token_b_address.address()),
+            (
+                &admin,
+                &token_a_address.address(),
+                &token_b_address.address(),
+                None::<Address>,
+                None::<Address>,
+                0u32,
+            ),
         );
     } else {
         let _ = env.register(
             LiquidityPool,
-            (&token_b_address.address(), &token_a_address.address()),
+            (
+                &admin,
+                &token_b_address.address(),
+                &token_a_address.address(),
+                None::<Address>,
+                None::<Address>,
+                0u32,
+            ),
         );
     }
 }
@@ -114,6 +252,34 @@ fn test_first_deposit() {
     assert_eq!(shares, 1000); // sqrt(1000 * 1000) = 1000
 }
 
+#[test]
+#[should_panic(expected = "amount_a less than min")]
+fn test_first_deposit_enforces_minimums() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 10_000);
+
+    test.pool.deposit(&test.user, &10_000, &10_001, &10_000, &10_000);
+}
+
+#[test]
+fn test_deposit_and_withdraw_emit_share_events() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 10_000);
+
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.assert_pool_event(&Mint {
+        to: test.user.clone(),
+        to_muxed_id: None,
+        amount: 10_000,
+    });
+
+    test.pool.withdraw(&test.user, &4_000, &0, &0);
+    test.assert_pool_event(&Burn {
+        from: test.user.clone(),
+        amount: 4_000,
+    });
+}
+
 #[test]
 fn test_deposit_maintains_ratio() {
     let test = LiquidityPoolTest::setup();
@@ -137,6 +303,62 @@ fn test_deposit_maintains_ratio() {
     assert_eq!(shares2, 500);
 }
 
+#[test]
+fn test_pool_info() {
+    let test = LiquidityPoolTest::setup();
+
+    assert_eq!(test.pool.total_shares(), 0);
+    assert_eq!(test.pool.pool_info(&test.user), (0, 0, 0, 0));
+
+    test.mint_tokens(&test.user, 2000);
+    test.pool.deposit(&test.user, &2000, &2000, &500, &500);
+
+    let user2 = Address::generate(&test.env);
+    test.mint_tokens(&user2, 1000);
+    test.pool.deposit(&user2, &1000, &1000, &250, &250);
+
+    assert_eq!(test.pool.total_shares(), 1500);
+    assert_eq!(test.pool.pool_info(&test.user), (3000, 750, 1500, 1000));
+    assert_eq!(test.pool.pool_info(&user2), (3000, 750, 1500, 500));
+}
+
+#[test]
+fn test_quote_deposit_empty_pool() {
+    let test = LiquidityPoolTest::setup();
+
+    // Any ratio, geometric mean shares
+    assert_eq!(test.pool.quote_deposit(&1000, &4000), (1000, 4000, 2000));
+
+    test.mint_tokens(&test.user, 4000);
+    test.pool.deposit(&test.user, &1000, &1000, &4000, &4000);
+    assert_eq!(test.pool.balance_shares(&test.user), 2000);
+}
+
+#[test]
+fn test_quote_deposit_matches_deposit() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 3000);
+    test.pool.deposit(&test.user, &1000, &1000, &2000, &2000);
+    test.pool.swap(&test.user, &false, &300, &i128::MAX, &None);
+
+    // Both branches: B capped by the ratio, then A capped by the ratio
+    for (desired_a, desired_b) in [(500, 5000), (5000, 700)] {
+        let (amount_a, amount_b, shares) = test.pool.quote_deposit(&desired_a, &desired_b);
+        let (reserves_before, total_before) = (test.pool.get_rsrvs(), test.pool.total_shares());
+
+        let depositor = Address::generate(&test.env);
+        test.mint_tokens(&depositor, 5000);
+        test.pool.deposit(&depositor, &desired_a, &0, &desired_b, &0);
+
+        assert_eq!(test.pool.balance_shares(&depositor), shares);
+        assert_eq!(test.token_a.balance(&depositor), 5000 - amount_a);
+        assert_eq!(test.token_b.balance(&depositor), 5000 - amount_b);
+        assert_eq!(test.pool.get_rsrvs(), (reserves_before.0 + amount_a, reserves_before.1 + amount_b));
+        assert_eq!(test.pool.total_shares(), total_before + shares);
+    }
+}
+
 #[test]
 fn test_deposit_adjusts_to_pool_ratio() {
     let test = LiquidityPoolTest::setup();
@@ -203,7 +425,7 @@ fn test_swap_a_for_b() {
 
     // Swap for 9,000 B (buy_a = false means selling A, buying B)
     let desired_out = 9_000;
-    test.pool.swap(&swapper, &false, &desired_out, &i128::MAX);
+    test.pool.swap(&swapper, &false, &desired_out, &i128::MAX, &None);
 
     // Check reserves changed appropriately
     let (reserve_a, reserve_b) = test.pool.get_rsrvs();
@@ -227,7 +449,7 @@ fn test_swap_b_for_a() {
 
     // Swap B for 9,000 A (buy_a = true means buying A, selling B)
     let desired_out = 9_000;
-    test.pool.swap(&swapper, &true, &desired_out, &i128::MAX);
+    test.pool.swap(&swapper, &true, &desired_out, &i128::MAX, &None);
 
     // Check reserves changed appropriately
     let (reserve_a, reserve_b) = test.pool.get_rsrvs();
@@ -250,7 +472,47 @@ fn test_swap_fails_insufficient_liquidity() {
     let token_a_admin = StellarAssetClient::new(&test.env, &test.token_a.address);
     token_a_admin.mint(&swapper, &10_000);
 
-    test.pool.swap(&swapper, &false, &1_001, &i128::MAX);
+    test.pool.swap(&swapper, &false, &1_001, &i128::MAX, &None);
+}
+
+#[test]
+#[should_panic(expected = "out must be strictly positive")]
+fn test_swap_fails_with_zero_out() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 20_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.swap(&test.user, &false, &0, &10_000, &None);
+}
+
+#[test]
+#[should_panic(expected = "out must be strictly positive")]
+fn test_swap_fails_with_negative_out() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 20_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.swap(&test.user, &false, &-1, &10_000, &None);
+}
+
+#[test]
+#[should_panic(expected = "in_max must be strictly positive")]
+fn test_swap_fails_with_zero_in_max() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 20_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.swap(&test.user, &false, &100, &0, &None);
+}
+
+#[test]
+#[should_panic(expected = "in_max must be strictly positive")]
+fn test_swap_fails_with_negative_in_max() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 20_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.swap(&test.user, &false, &100, &-1, &None);
 }
 
 #[test]
@@ -268,7 +530,7 @@ fn test_swap_fails_slippage_protection() {
     token_a_admin.mint(&swapper, &10_000);
 
     // Want 9,000 B but only willing to pay max 100 A (way too low)
-    test.pool.swap(&swapper, &false, &9_000, &100);
+    test.pool.swap(&swapper, &false, &9_000, &100, &None);
 }
 
 #[test]
@@ -286,7 +548,7 @@ fn test_swap_respects_fee() {
     let swapper = Address::generate(&test.env);
     let token_a_admin = StellarAssetClient::new(&test.env, &test.token_a.address);
     token_a_admin.mint(&swapper, &10_000);
-    test.pool.swap(&swapper, &false, &9_000, &i128::MAX);
+    test.pool.swap(&swapper, &false, &9_000, &i128::MAX, &None);
 
     // After swap, k should be slightly higher due to fees
     let (final_a, final_b) = test.pool.get_rsrvs();
@@ -344,6 +606,42 @@ fn test_withdraw_partial_liquidity() {
     assert_eq!(reserve_b, 5_000);
 }
 
+#[test]
+fn test_withdraw_all() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+
+    let user2 = Address::generate(&test.env);
+    test.mint_tokens(&user2, 5_000);
+    test.pool.deposit(&user2, &5_000, &5_000, &5_000, &5_000);
+
+    let shares = test.pool.balance_shares(&test.user);
+    let (out_a, out_b, burned) = test.pool.withdraw_all(&test.user, &0, &0, &u64::MAX);
+
+    // The first provider owned two thirds of the pool
+    assert_eq!(burned, shares);
+    assert_eq!(out_a, 10_000);
+    assert_eq!(out_b, 10_000);
+    assert_eq!(test.pool.balance_shares(&test.user), 0);
+    assert_eq!(test.pool.total_shares(), 5_000);
+    assert_eq!(test.pool.get_rsrvs(), (5_000, 5_000));
+
+    // The last provider empties the pool
+    let (out_a, out_b, burned) = test.pool.withdraw_all(&user2, &5_000, &5_000, &u64::MAX);
+    assert_eq!((out_a, out_b, burned), (5_000, 5_000, 5_000));
+    assert_eq!(test.pool.get_rsrvs(), (0, 0));
+    assert_eq!(test.pool.total_shares(), 0);
+}
+
+#[test]
+#[should_panic(expected = "insufficient shares")]
+fn test_withdraw_all_without_shares() {
+    let test = LiquidityPoolTest::setup();
+    test.pool.withdraw_all(&test.user, &0, &0, &u64::MAX);
+}
+
 #[test]
 #[should_panic(expected = "insufficient shares")]
 fn test_withdraw_fails_insufficient_shares() {
@@ -359,6 +657,26 @@ fn test_withdraw_fails_insufficient_shares() {
     test.pool.withdraw(&test.user, &(shares + 1), &0, &0);
 }
 
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_withdraw_fails_with_zero_shares() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.withdraw(&test.user, &0, &0, &0);
+}
+
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_withdraw_fails_with_negative_shares() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.withdraw(&test.user, &-1, &0, &0);
+}
+
 #[test]
 #[should_panic(expected = "min not satisfied")]
 fn test_withdraw_fails_minimum_not_met() {
@@ -374,6 +692,119 @@ fn test_withdraw_fails_minimum_not_met() {
     test.pool.withdraw(&test.user, &shares, &20_000, &20_000);
 }
 
+// Pool of 20_000 A / 80_000 B split evenly between two LPs with 20_000 shares each
+fn setup_pool_for_single_withdraw<'a>() -> LiquidityPoolTest<'a> {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 40_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &40_000, &40_000);
+
+    let user2 = Address::generate(&test.env);
+    test.mint_tokens(&user2, 40_000);
+    test.pool.deposit(&user2, &10_000, &10_000, &40_000, &40_000);
+    test
+}
+
+#[test]
+fn test_withdraw_single_matches_withdraw_then_swap() {
+    // Single-sided exit into A
+    let single = setup_pool_for_single_withdraw();
+    let a_before = single.token_a.balance(&single.user);
+    let b_before = single.token_b.balance(&single.user);
+    let out = single.pool.withdraw_single(&single.user, &10_000, &true, &0, &u64::MAX);
+    assert_eq!(single.token_a.balance(&single.user), a_before + out);
+    assert_eq!(single.token_b.balance(&single.user), b_before);
+
+    // The same exit done by hand: withdraw both sides, then sell all of B for A
+    let manual = setup_pool_for_single_withdraw();
+    let (out_a, out_b) = manual.pool.withdraw(&manual.user, &10_000, &0, &0);
+    assert_eq!((out_a, out_b), (5_000, 20_000));
+    manual.pool.swap(&manual.user, &true, &(out - out_a), &out_b, &None);
+
+    // Buying even one more A would cost more B than the withdrawal returned
+    let result = manual.pool.try_swap(&manual.user, &true, &1, &0, &None);
+    assert!(result.is_err());
+
+    // Both routes pay out the same A; by hand a rounding remainder of B stays with the user
+    let (single_a, single_b) = single.pool.get_rsrvs();
+    let (manual_a, manual_b) = manual.pool.get_rsrvs();
+    assert_eq!(single_a, manual_a);
+    let leftover_b = single_b - manual_b;
+    assert!((0..10).contains(&leftover_b));
+    assert_eq!(manual.token_b.balance(&manual.user), b_before + leftover_b);
+}
+
+#[test]
+fn test_withdraw_single_into_b() {
+    let test = setup_pool_for_single_withdraw();
+
+    let b_before = test.token_b.balance(&test.user);
+    let out = test.pool.withdraw_single(&test.user, &20_000, &false, &0, &u64::MAX);
+
+    // 40_000 B withdrawn plus the 10_000 A sold at a discount to the 1:4 price
+    assert!(out > 40_000 && out < 80_000);
+    assert_eq!(test.token_b.balance(&test.user), b_before + out);
+    assert_eq!(test.pool.balance_shares(&test.user), 0);
+    assert_eq!(test.pool.get_rsrvs(), (20_000, 80_000 - out));
+}
+
+#[test]
+#[should_panic(expected = "min not satisfied")]
+fn test_withdraw_single_min_out() {
+    let test = setup_pool_for_single_withdraw();
+    test.pool.withdraw_single(&test.user, &10_000, &true, &10_000, &u64::MAX);
+}
+
+#[test]
+#[should_panic(expected = "deadline passed")]
+fn test_withdraw_single_deadline() {
+    let test = setup_pool_for_single_withdraw();
+    test.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    test.pool.withdraw_single(&test.user, &10_000, &true, &0, &999);
+}
+
+#[test]
+#[should_panic(expected = "pool emptied, use withdraw")]
+fn test_withdraw_single_last_lp_panics() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    let shares = test.pool.balance_shares(&test.user);
+    test.pool.withdraw_single(&test.user, &shares, &false, &0, &u64::MAX);
+}
+
+#[test]
+fn test_withdraw_single_last_lp_rejected() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    let shares = test.pool.balance_shares(&test.user);
+
+    // Every share out leaves no reserves to sell the other side into
+    let result = test.pool.try_withdraw_single(&test.user, &shares, &true, &0, &u64::MAX);
+    assert!(result.is_err());
+    assert_eq!(test.pool.get_rsrvs(), (10_000, 10_000));
+
+    // The plain withdrawal still exits, and the emptied pool takes a fresh deposit
+    test.pool.withdraw(&test.user, &shares, &0, &0);
+    assert_eq!(test.pool.get_rsrvs(), (0, 0));
+    test.pool.deposit(&test.user, &1_000, &1_000, &4_000, &4_000);
+    assert_eq!(test.pool.get_rsrvs(), (1_000, 4_000));
+}
+
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_withdraw_single_zero_shares() {
+    let test = setup_pool_for_single_withdraw();
+    test.pool.withdraw_single(&test.user, &0, &true, &0, &u64::MAX);
+}
+
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_withdraw_single_negative_shares() {
+    let test = setup_pool_for_single_withdraw();
+    test.pool.withdraw_single(&test.user, &-1, &true, &0, &u64::MAX);
+}
+
 #[test]
 fn test_multiple_liquidity_providers() {
     let test = LiquidityPoolTest::setup();
@@ -417,11 +848,11 @@ fn test_withdraw_after_profitable_swaps() {
         if i % 2 == 0 {
             let token_a_admin = StellarAssetClient::new(&test.env, &test.token_a.address);
             token_a_admin.mint(&swapper, &50_000);
-            test.pool.swap(&swapper, &false, &5_000, &i128::MAX);
+            test.pool.swap(&swapper, &false, &5_000, &i128::MAX, &None);
         } else {
             let token_b_admin = StellarAssetClient::new(&test.env, &test.token_b.address);
             token_b_admin.mint(&swapper, &50_000);
-            test.pool.swap(&swapper, &true, &5_000, &i128::MAX);
+            test.pool.swap(&swapper, &true, &5_000, &i128::MAX, &None);
         }
     }
 
@@ -470,7 +901,7 @@ fn test_swap_both_directions_maintains_balance() {
     let swapper1 = Address::generate(&test.env);
     let token_a_admin = StellarAssetClient::new(&test.env, &test.token_a.address);
     token_a_admin.mint(&swapper1, &5_000);
-    test.pool.swap(&swapper1, &false, &4_500, &i128::MAX);
+    test.pool.swap(&swapper1, &false, &4_500, &i128::MAX, &None);
 
     let (mid_reserve_a, mid_reserve_b) = test.pool.get_rsrvs();
 
@@ -478,7 +909,7 @@ fn test_swap_both_directions_maintains_balance() {
     let swapper2 = Address::generate(&test.env);
     let token_b_admin = StellarAssetClient::new(&test.env, &test.token_b.address);
     token_b_admin.mint(&swapper2, &5_000);
-    test.pool.swap(&swapper2, &true, &4_500, &i128::MAX);
+    test.pool.swap(&swapper2, &true, &4_500, &i128::MAX, &None);
 
     let (final_reserve_a, final_reserve_b) = test.pool.get_rsrvs();
 
@@ -492,6 +923,212 @@ fn test_swap_both_directions_maintains_balance() {
     assert!(ratio_final < ratio_mid);
 }
 
+#[test]
+fn test_spot_price() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    assert_eq!(test.pool.spot_price(&true), SPOT_PRICE_SCALE);
+    assert_eq!(test.pool.spot_price(&false), SPOT_PRICE_SCALE);
+
+    // Buying B with A makes B scarcer: A buys less B, B buys more A
+    let swapper = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&swapper, &20_000);
+    test.pool.swap(&swapper, &false, &9_000, &i128::MAX, &None);
+
+    assert!(test.pool.spot_price(&false) < SPOT_PRICE_SCALE);
+    assert!(test.pool.spot_price(&true) > SPOT_PRICE_SCALE);
+}
+
+#[test]
+#[should_panic(expected = "pool is empty")]
+fn test_spot_price_empty_pool() {
+    let test = LiquidityPoolTest::setup();
+    test.pool.spot_price(&true);
+}
+
+// Round trips of swaps between two fresh swappers, growing k by the fees alone
+fn trade_volume(test: &LiquidityPoolTest, round_trips: u32) {
+    let swapper = Address::generate(&test.env);
+    test.mint_tokens(&swapper, 1_000_000_000);
+    for _ in 0..round_trips {
+        test.pool.swap(&swapper, &false, &10_000_000, &i128::MAX, &None);
+        test.pool.swap(&swapper, &true, &10_000_000, &i128::MAX, &None);
+    }
+}
+
+#[test]
+fn test_protocol_fee_accrues_with_volume() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &1_000_000_000, &0, &1_000_000_000, &0);
+    let lp_shares = test.pool.balance_shares(&test.user);
+
+    trade_volume(&test, 10);
+    let first = test.pool.balance_shares(&test.protocol_fee_recipient);
+    assert!(first > 0);
+
+    // Twice the volume, about twice the shares
+    trade_volume(&test, 10);
+    let second = test.pool.balance_shares(&test.protocol_fee_recipient);
+    let doubled = second * 100 / first;
+    assert!((195..=205).contains(&doubled));
+
+    // The recipient is minted shares, the LPs keep theirs
+    assert_eq!(test.pool.balance_shares(&test.user), lp_shares);
+    assert_eq!(test.pool.total_shares(), lp_shares + second);
+}
+
+#[test]
+fn test_protocol_fee_is_a_fraction_of_lp_fee() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &1_000_000_000, &0, &1_000_000_000, &0);
+    let root_k_before = test.pool.get_k().sqrt();
+
+    trade_volume(&test, 10);
+
+    // The recipient's shares are worth 1/6 of the sqrt(k) growth
+    let root_k_after = test.pool.get_k().sqrt();
+    let recipient_value = root_k_after * test.pool.balance_shares(&test.protocol_fee_recipient)
+        / test.pool.total_shares();
+    let fee_growth = root_k_after - root_k_before;
+    assert!((fee_growth / 6 - recipient_value).abs() <= fee_growth / 100);
+}
+
+#[test]
+fn test_quote_deposit_includes_pending_protocol_fee() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &100_000_000, &0, &100_000_000, &0);
+
+    // A donation synced into the reserves grows k without a fee checkpoint
+    test.token_a.transfer(&test.user, &test.pool.address, &10_000_000);
+    test.token_b.transfer(&test.user, &test.pool.address, &10_000_000);
+    test.pool.sync();
+
+    let (amount_a, amount_b, shares) = test.pool.quote_deposit(&10_000_000, &10_000_000);
+    let depositor = Address::generate(&test.env);
+    test.mint_tokens(&depositor, 10_000_000);
+    test.pool.deposit(&depositor, &amount_a, &0, &amount_b, &0);
+
+    // The deposit minted the fee first, the quote accounted for the dilution
+    assert!(test.pool.balance_shares(&test.protocol_fee_recipient) > 0);
+    assert_eq!(test.pool.balance_shares(&depositor), shares);
+}
+
+#[test]
+fn test_set_fee_recipient_routes_later_fees() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &1_000_000_000, &0, &1_000_000_000, &0);
+    trade_volume(&test, 10);
+    let old_shares = test.pool.balance_shares(&test.protocol_fee_recipient);
+    assert!(old_shares > 0);
+
+    let new_recipient = Address::generate(&test.env);
+    test.pool.set_fee_recipient(&Some(new_recipient.clone()));
+    test.assert_pool_event(&FeeRecipientSet {
+        recipient: Some(new_recipient.clone()),
+    });
+    assert_eq!(test.pool.get_fee_recipient(), Some(new_recipient.clone()));
+
+    // Fees from swaps after the change go to the new recipient only
+    trade_volume(&test, 10);
+    assert_eq!(test.pool.balance_shares(&test.protocol_fee_recipient), old_shares);
+    let new_shares = test.pool.balance_shares(&new_recipient);
+    let ratio = new_shares * 100 / old_shares;
+    assert!((95..=105).contains(&ratio));
+}
+
+#[test]
+fn test_set_fee_recipient_none_turns_fee_off() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &1_000_000_000, &0, &1_000_000_000, &0);
+    trade_volume(&test, 10);
+    let fee_shares = test.pool.balance_shares(&test.protocol_fee_recipient);
+
+    test.pool.set_fee_recipient(&None);
+    test.assert_pool_event(&FeeRecipientSet { recipient: None });
+    assert_eq!(test.pool.get_fee_recipient(), None);
+
+    trade_volume(&test, 10);
+    assert_eq!(test.pool.balance_shares(&test.protocol_fee_recipient), fee_shares);
+
+    // Switched back on, only growth from then on is charged
+    test.pool.set_fee_recipient(&Some(test.protocol_fee_recipient.clone()));
+    test.mint_tokens(&test.user, 1_000);
+    test.pool.deposit(&test.user, &1_000, &0, &1_000, &0);
+    assert_eq!(test.pool.balance_shares(&test.protocol_fee_recipient), fee_shares);
+}
+
+#[test]
+fn test_set_fee_recipient_requires_admin() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    let new_recipient = Address::generate(&test.env);
+
+    test.env.mock_auths(&[MockAuth {
+        address: &test.user,
+        invoke: &MockAuthInvoke {
+            contract: &test.pool.address,
+            fn_name: "set_fee_recipient",
+            args: (Some(new_recipient.clone()),).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(test.pool.try_set_fee_recipient(&Some(new_recipient)).is_err());
+    assert_eq!(test.pool.get_fee_recipient(), Some(test.protocol_fee_recipient.clone()));
+}
+
+#[test]
+#[should_panic(expected = "protocol fee fraction must be positive")]
+fn test_set_fee_recipient_without_fraction() {
+    let test = LiquidityPoolTest::setup();
+    test.pool.set_fee_recipient(&Some(test.protocol_fee_recipient.clone()));
+}
+
+#[test]
+fn test_protocol_fee_off_mints_nothing() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 1_000_000_000);
+    test.pool.deposit(&test.user, &1_000_000_000, &0, &1_000_000_000, &0);
+    let lp_shares = test.pool.balance_shares(&test.user);
+
+    trade_volume(&test, 10);
+
+    assert_eq!(test.pool.balance_shares(&test.protocol_fee_recipient), 0);
+    assert_eq!(test.pool.total_shares(), lp_shares);
+}
+
+#[test]
+fn test_swap_within_max_impact() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // Buying 100 of 100k moves the price by about 20 bps
+    let swapper = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&swapper, &1_000);
+    test.pool.swap(&swapper, &false, &100, &i128::MAX, &Some(50));
+
+    assert_eq!(test.token_b.balance(&swapper), 100);
+}
+
+#[test]
+#[should_panic(expected = "price impact too high")]
+fn test_swap_over_max_impact_reverts() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // Buying 9k of 100k moves the price by about 17%
+    let swapper = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&swapper, &20_000);
+    test.pool.swap(&swapper, &false, &9_000, &i128::MAX, &Some(50));
+}
+
 #[test]
 fn test_price_impact() {
     let test = LiquidityPoolTest::setup();
@@ -505,7 +1142,7 @@ fn test_price_impact() {
     let token_a_admin = StellarAssetClient::new(&test.env, &test.token_a.address);
     token_a_admin.mint(&swapper1, &1_000);
 
-    test.pool.swap(&swapper1, &false, &900, &i128::MAX);
+    test.pool.swap(&swapper1, &false, &900, &i128::MAX, &None);
     let (reserve_a_after_small, reserve_b_after_small) = test.pool.get_rsrvs();
 
     // Calculate price impact for small swap
@@ -523,7 +1160,7 @@ fn test_price_impact() {
     let token_a_admin2 = StellarAssetClient::new(&test2.env, &test2.token_a.address);
     token_a_admin2.mint(&swapper2, &10_000);
 
-    test2.pool.swap(&swapper2, &false, &9_000, &i128::MAX);
+    test2.pool.swap(&swapper2, &false, &9_000, &i128::MAX, &None);
     let (reserve_a_after_large, reserve_b_after_large) = test2.pool.get_rsrvs();
 
     // Calculate price impact for large swap
@@ -532,3 +1169,892 @@ fn test_price_impact() {
     // Large swap should deviate more from 100 than small swap
     assert!(large_ratio > small_ratio);
 }
+
+#[test]
+fn test_sync_absorbs_donation() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 1000);
+    test.pool.deposit(&test.user, &1000, &1000, &1000, &1000);
+
+    // Donate straight to the pool, bypassing deposit
+    let donor = Address::generate(&test.env);
+    test.mint_tokens(&donor, 300);
+    test.token_a.transfer(&donor, &test.pool.address, &300);
+    test.token_b.transfer(&donor, &test.pool.address, &100);
+    assert_eq!(test.pool.get_rsrvs(), (1000, 1000));
+
+    test.pool.sync();
+    assert_eq!(test.pool.get_rsrvs(), (1300, 1100));
+
+    // LPs own the donation
+    let (out_a, out_b) = test.pool.withdraw(&test.user, &1000, &0, &0);
+    assert_eq!((out_a, out_b), (1300, 1100));
+}
+
+#[test]
+fn test_skim_returns_surplus() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 1000);
+    test.pool.deposit(&test.user, &1000, &1000, &1000, &1000);
+
+    let donor = Address::generate(&test.env);
+    test.mint_tokens(&donor, 300);
+    test.token_a.transfer(&donor, &test.pool.address, &300);
+
+    let recipient = Address::generate(&test.env);
+    let (excess_a, excess_b) = test.pool.skim(&recipient);
+    assert_eq!((excess_a, excess_b), (300, 0));
+    assert_eq!(test.token_a.balance(&recipient), 300);
+    assert_eq!(test.token_b.balance(&recipient), 0);
+
+    // Reserves and balances agree again
+    assert_eq!(test.pool.get_rsrvs(), (1000, 1000));
+    assert_eq!(test.token_a.balance(&test.pool.address), 1000);
+    assert_eq!(test.token_b.balance(&test.pool.address), 1000);
+}
+
+// Flash swap receiver that pays back a configured amount of one token from its own balance
+#[contract]
+pub struct MockFlashReceiver;
+
+#[contractimpl]
+impl MockFlashReceiver {
+    pub fn set_repayment(e: Env, token: Address, amount: i128) {
+        e.storage().instance().set(&symbol_short!("repay"), &(token, amount));
+    }
+
+    pub fn last_data(e: Env) -> Bytes {
+        e.storage().instance().get(&symbol_short!("data")).unwrap()
+    }
+
+    pub fn flash_callback(e: Env, pool: Address, _buy_a: bool, _amount: i128, data: Bytes) {
+        e.storage().instance().set(&symbol_short!("data"), &data);
+        let (token, amount): (Address, i128) = e.storage().instance().get(&symbol_short!("repay")).unwrap();
+        token::Client::new(&e, &token).transfer(&e.current_contract_address(), &pool, &amount);
+    }
+}
+
+// 1,000,000:1,000,000 pool and a receiver holding `float` of each token to cover the fee
+fn setup_flash_swap<'a>(float: i128) -> (LiquidityPoolTest<'a>, Address) {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 1_000_000);
+    test.pool.deposit(&test.user, &1_000_000, &1_000_000, &1_000_000, &1_000_000);
+
+    let receiver = test.env.register(MockFlashReceiver, ());
+    test.mint_tokens(&receiver, float);
+    (test, receiver)
+}
+
+#[test]
+fn test_flash_swap_repaid_with_fee() {
+    let (test, receiver) = setup_flash_swap(100);
+    let receiver_client = MockFlashReceiverClient::new(&test.env, &receiver);
+
+    // Borrow A and return it with the 0.3% fee on top
+    let out = 10_000;
+    let repayment = out * 1000 / 997 + 1;
+    receiver_client.set_repayment(&test.token_a.address, &repayment);
+    let data = Bytes::from_array(&test.env, &[1, 2, 3]);
+    test.pool.flash_swap(&receiver, &true, &out, &data);
+
+    assert_eq!(receiver_client.last_data(), data);
+    assert_eq!(test.token_a.balance(&receiver), 100 - (repayment - out));
+    assert_eq!(test.pool.get_rsrvs(), (1_000_000 + repayment - out, 1_000_000));
+    assert!(test.pool.get_k() > 1_000_000 * 1_000_000);
+}
+
+#[test]
+fn test_flash_swap_repaid_in_other_token() {
+    let (test, receiver) = setup_flash_swap(20_000);
+    let receiver_client = MockFlashReceiverClient::new(&test.env, &receiver);
+
+    // Borrowing A and paying in B costs what swap would charge
+    let out = 10_000;
+    let n = 1_000_000 * out * 1000;
+    let d = (1_000_000 - out) * 997;
+    let sell_amount = n / d + 1;
+    receiver_client.set_repayment(&test.token_b.address, &sell_amount);
+    test.pool.flash_swap(&receiver, &true, &out, &Bytes::new(&test.env));
+
+    assert_eq!(test.token_a.balance(&receiver), 20_000 + out);
+    assert_eq!(test.pool.get_rsrvs(), (1_000_000 - out, 1_000_000 + sell_amount));
+}
+
+#[test]
+fn test_flash_swap_underpaid_reverts() {
+    let (test, receiver) = setup_flash_swap(100);
+    let receiver_client = MockFlashReceiverClient::new(&test.env, &receiver);
+
+    // Returning the principal without the fee breaks the invariant
+    let out = 10_000;
+    receiver_client.set_repayment(&test.token_a.address, &out);
+    let result = test.pool.try_flash_swap(&receiver, &true, &out, &Bytes::new(&test.env));
+    assert!(result.is_err());
+
+    assert_eq!(test.token_a.balance(&receiver), 100);
+    assert_eq!(test.token_a.balance(&test.pool.address), 1_000_000);
+    assert_eq!(test.pool.get_rsrvs(), (1_000_000, 1_000_000));
+}
+
+#[test]
+fn test_swap_and_flash_swap_at_13_decimal_magnitudes() {
+    let test = LiquidityPoolTest::setup();
+
+    // 10M PT at 13 decimals against 10M of a 7-decimal asset: the fee-scaled
+    // invariant products reach 1e40, past i128
+    let pt_reserve = 10_000_000 * 10i128.pow(13);
+    let asset_reserve = 10_000_000 * 10i128.pow(7);
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&test.user, &(pt_reserve * 2));
+    StellarAssetClient::new(&test.env, &test.token_b.address).mint(&test.user, &(asset_reserve * 2));
+    test.pool.deposit(&test.user, &pt_reserve, &0, &asset_reserve, &0);
+
+    // Buy 1% of each side
+    test.pool.swap(&test.user, &true, &(pt_reserve / 100), &i128::MAX, &None);
+    test.pool.swap(&test.user, &false, &(asset_reserve / 100), &i128::MAX, &None);
+    let k = test.pool.assert_k_nondecreasing();
+
+    // Flash-borrow 1% of the PT and pay it back with the fee
+    let receiver = test.env.register(MockFlashReceiver, ());
+    let out = pt_reserve / 100;
+    let repayment = out * 1000 / 997 + 1;
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&receiver, &(repayment - out));
+    MockFlashReceiverClient::new(&test.env, &receiver).set_repayment(&test.token_a.address, &repayment);
+    test.pool.flash_swap(&receiver, &true, &out, &Bytes::new(&test.env));
+
+    assert!(test.pool.assert_k_nondecreasing() > k);
+    assert_eq!(test.token_a.balance(&receiver), 0);
+}
+
+#[test]
+fn test_k_nondecreasing_across_swaps() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    assert_eq!(test.pool.get_k(), 100_000_000);
+
+    let mut last_k = test.pool.assert_k_nondecreasing();
+    for i in 0..10 {
+        test.pool.swap(&test.user, &(i % 2 == 0), &(100 + i as i128 * 37), &10_000, &None);
+
+        let k = test.pool.assert_k_nondecreasing();
+        assert!(k >= last_k);
+        assert_eq!(k, test.pool.get_k());
+        last_k = k;
+    }
+
+    // Fees accumulate, so k strictly grew overall
+    assert!(last_k > 100_000_000);
+}
+
+#[test]
+fn test_liquidity_moves_rebaseline_k() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 20_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+    test.pool.swap(&test.user, &true, &100, &10_000, &None);
+
+    // A withdrawal shrinks k and re-baselines the checkpoint, later trades check against it
+    test.pool.withdraw(&test.user, &5_000, &0, &0);
+    assert_eq!(test.pool.assert_k_nondecreasing(), test.pool.get_k());
+    test.pool.swap(&test.user, &false, &100, &10_000, &None);
+    test.pool.deposit(&test.user, &1_000, &0, &1_000, &0);
+    assert_eq!(test.pool.assert_k_nondecreasing(), test.pool.get_k());
+}
+
+#[test]
+#[should_panic(expected = "k decreased")]
+fn test_assert_k_nondecreasing_detects_drop() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 10_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+
+    // Reserves shrinking outside any liquidity move, as a fee accounting bug would
+    test.env.as_contract(&test.pool.address, || {
+        crate::storage::put_reserve_a(&test.env, 9_000);
+    });
+    test.pool.assert_k_nondecreasing();
+}
+
+// Small deterministic generator (SplitMix64) so a failing run can be replayed from its seed
+struct Prng(u64);
+
+impl Prng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform-ish value in [low, high], drawing 128 bits so ranges past u64 are covered
+    fn range(&mut self, low: i128, high: i128) -> i128 {
+        let wide = ((self.next() as u128) << 64) | self.next() as u128;
+        low + (wide % (high - low + 1) as u128) as i128
+    }
+}
+
+// a * b / c in 256 bits, as the pool computes it
+fn mul_div(env: &Env, a: i128, b: i128, c: i128) -> i128 {
+    I256::from_i128(env, a)
+        .mul(&I256::from_i128(env, b))
+        .div(&I256::from_i128(env, c))
+        .to_i128()
+        .unwrap()
+}
+
+#[test]
+fn test_random_operations_preserve_invariants() {
+    let test = LiquidityPoolTest::setup_with(None, Some(6));
+    test.env.cost_estimate().budget().reset_unlimited();
+    let env = &test.env;
+    let recipient = &test.protocol_fee_recipient;
+
+    // Token A sized like a 13-decimal PT, token B like a 7-decimal asset
+    let (max_a, max_b) = (100_000_000 * 10i128.pow(12), 100_000_000 * 10i128.pow(6));
+    let (minted_a, minted_b) = (max_a * 100, max_b * 100);
+    let mint = |to: &Address| {
+        StellarAssetClient::new(env, &test.token_a.address).mint(to, &minted_a);
+        StellarAssetClient::new(env, &test.token_b.address).mint(to, &minted_b);
+    };
+
+    let users = [
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    for user in users.iter() {
+        mint(user);
+    }
+    let receiver = env.register(MockFlashReceiver, ());
+    let receiver_client = MockFlashReceiverClient::new(env, &receiver);
+    mint(&receiver);
+
+    let mut rng = Prng(0x5EED);
+    for _ in 0..2_000 {
+        let user = &users[rng.range(0, 2) as usize];
+        let (reserve_a, reserve_b, total_shares, user_shares) = test.pool.pool_info(user);
+        let k = reserve_a * reserve_b;
+        let fee_shares = test.pool.balance_shares(recipient);
+
+        match rng.range(0, 4) {
+            // Deposit, skipping amounts the current ratio would round to zero
+            0 => {
+                let desired_a = rng.range(1, max_a);
+                let desired_b = rng.range(1, max_b);
+                let (amount_a, amount_b, _) = test.pool.quote_deposit(&desired_a, &desired_b);
+                if amount_a <= 0 || amount_b <= 0 {
+                    continue;
+                }
+                test.pool.deposit(user, &desired_a, &0, &desired_b, &0);
+            }
+            // Withdraw part or all of the user's shares at exactly their pro-rata value,
+            // counting the protocol fee shares minted first
+            1 => {
+                if user_shares == 0 {
+                    continue;
+                }
+                let share_amount = rng.range(1, user_shares);
+                let (out_a, out_b) = test.pool.withdraw(user, &share_amount, &0, &0);
+                let minted = test.pool.balance_shares(recipient) - fee_shares;
+                assert_eq!(out_a, mul_div(env, reserve_a, share_amount, total_shares + minted));
+                assert_eq!(out_b, mul_div(env, reserve_b, share_amount, total_shares + minted));
+            }
+            // Swap up to a quarter of the bought reserve, k must not drop
+            2 => {
+                let buy_a = rng.range(0, 1) == 0;
+                let reserve_buy = if buy_a { reserve_a } else { reserve_b };
+                if reserve_buy < 4 {
+                    continue;
+                }
+                let out = rng.range(1, reserve_buy / 4);
+                test.pool.swap(user, &buy_a, &out, &i128::MAX, &None);
+
+                let (new_a, new_b, _, _) = test.pool.pool_info(user);
+                assert!(new_a * new_b >= k, "swap decreased k");
+            }
+            // Flash-borrow up to a quarter of a reserve and repay in either token at the
+            // minimum the pool accepts
+            3 => {
+                let buy_a = rng.range(0, 1) == 0;
+                let (reserve_sell, reserve_buy) =
+                    if buy_a { (reserve_b, reserve_a) } else { (reserve_a, reserve_b) };
+                if reserve_buy < 4 {
+                    continue;
+                }
+                let out = rng.range(1, reserve_buy / 4);
+                let (token_buy, token_sell) = if buy_a {
+                    (&test.token_a.address, &test.token_b.address)
+                } else {
+                    (&test.token_b.address, &test.token_a.address)
+                };
+                if rng.range(0, 1) == 0 {
+                    receiver_client.set_repayment(token_buy, &(out * 1000 / 997 + 1));
+                } else {
+                    let sell_amount = mul_div(env, reserve_sell, out * 1000, (reserve_buy - out) * 997) + 1;
+                    receiver_client.set_repayment(token_sell, &sell_amount);
+                }
+                test.pool.flash_swap(&receiver, &buy_a, &out, &Bytes::new(env));
+
+                let (new_a, new_b, _, _) = test.pool.pool_info(user);
+                assert!(new_a * new_b >= k, "flash swap decreased k");
+            }
+            // Toggle the protocol fee
+            _ => {
+                let next = match test.pool.get_fee_recipient() {
+                    Some(_) => None,
+                    None => Some(recipient.clone()),
+                };
+                test.pool.set_fee_recipient(&next);
+            }
+        }
+
+        // Reserves are backed one to one by the pool's balances
+        let (new_a, new_b, new_shares, _) = test.pool.pool_info(user);
+        assert_eq!(test.token_a.balance(&test.pool.address), new_a);
+        assert_eq!(test.token_b.balance(&test.pool.address), new_b);
+
+        // Every share is held by someone, and only the fee recipient's appear without a deposit
+        let held: i128 = users.iter().map(|u| test.pool.balance_shares(u)).sum();
+        assert_eq!(held + test.pool.balance_shares(recipient), new_shares);
+
+        // Rounding always favors the pool: k per squared share never drops, unless
+        // protocol fee shares were minted, which dilute it by design
+        if total_shares > 0 && new_shares > 0 && test.pool.balance_shares(recipient) == fee_shares {
+            let before = I256::from_i128(env, k).mul(&I256::from_i128(env, new_shares).pow(2));
+            let after = I256::from_i128(env, new_a * new_b).mul(&I256::from_i128(env, total_shares).pow(2));
+            assert!(after >= before, "k per share decreased");
+        }
+    }
+
+    // Everyone exits, the pool is left empty and no tokens were created or lost
+    for holder in users.iter().chain([recipient]) {
+        let shares = test.pool.balance_shares(holder);
+        if shares > 0 {
+            test.pool.withdraw(holder, &shares, &0, &0);
+        }
+    }
+    assert_eq!(test.pool.pool_info(&users[0]), (0, 0, 0, 0));
+    let holders = users.iter().chain([recipient, &receiver]);
+    let total_a: i128 = holders.clone().map(|u| test.token_a.balance(u)).sum();
+    let total_b: i128 = holders.map(|u| test.token_b.balance(u)).sum();
+    assert_eq!(total_a, minted_a * 4);
+    assert_eq!(total_b, minted_b * 4);
+}
+
+#[test]
+fn test_pool_survives_long_ledger_gaps() {
+    let test = LiquidityPoolTest::setup();
+
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &10_000, &10_000, &10_000, &10_000);
+
+    // Weekly activity keeps the pool alive for three weeks
+    for _ in 0..3 {
+        test.advance_ledgers(5 * DAY_IN_LEDGERS);
+        test.pool.swap(&test.user, &true, &10, &100, &None);
+    }
+
+    // Pool and position were bumped well past the next gap
+    test.env.as_contract(&test.pool.address, || {
+        assert!(test.env.storage().instance().get_ttl() >= INSTANCE_LIFETIME_THRESHOLD);
+        let shares_key = DataKey::Shares(test.user.clone());
+        assert!(test.env.storage().persistent().get_ttl(&shares_key) > 5 * DAY_IN_LEDGERS);
+    });
+
+    // The LP position outlives the gaps and can still exit
+    test.advance_ledgers(5 * DAY_IN_LEDGERS);
+    assert_eq!(test.pool.balance_shares(&test.user), 10_000);
+    let (out_a, out_b) = test.pool.withdraw(&test.user, &10_000, &0, &0);
+    assert!(out_a > 0 && out_b > 0);
+}
+
+#[test]
+fn test_settle_matured_pool() {
+    let test = LiquidityPoolTest::setup_with_maturity(Some(1_000));
+
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // Trading moves the pool away from 1:1 before maturity
+    let swapper = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &test.token_a.address).mint(&swapper, &10_000);
+    test.pool.swap(&swapper, &false, &5_000, &i128::MAX, &None);
+
+    test.env.ledger().with_mut(|li| {
+        li.timestamp = 1_000;
+    });
+    test.pool.settle();
+    assert!(test.pool.is_settled());
+
+    // LPs redeem everything the pool holds
+    let (reserve_a, reserve_b) = test.pool.get_rsrvs();
+    let shares = test.pool.balance_shares(&test.user);
+    let (out_a, out_b) = test.pool.withdraw(&test.user, &shares, &0, &0);
+
+    assert_eq!((out_a, out_b), (reserve_a, reserve_b));
+    assert_eq!(test.token_a.balance(&test.user), reserve_a);
+    assert_eq!(test.token_b.balance(&test.user), reserve_b);
+}
+
+#[test]
+#[should_panic(expected = "maturity not reached")]
+fn test_settle_fails_before_maturity() {
+    let test = LiquidityPoolTest::setup_with_maturity(Some(1_000));
+
+    test.env.ledger().with_mut(|li| {
+        li.timestamp = 999;
+    });
+    test.pool.settle();
+}
+
+#[test]
+#[should_panic(expected = "pool has no yield manager")]
+fn test_settle_fails_without_yield_manager() {
+    let test = LiquidityPoolTest::setup();
+    test.pool.settle();
+}
+
+#[test]
+#[should_panic(expected = "pool is settled")]
+fn test_swap_fails_after_settle() {
+    let test = LiquidityPoolTest::setup_with_maturity(Some(1_000));
+
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.env.ledger().with_mut(|li| {
+        li.timestamp = 1_000;
+    });
+    test.pool.settle();
+
+    test.pool.swap(&test.user, &false, &100, &i128::MAX, &None);
+}
+
+#[test]
+#[should_panic(expected = "pool is paused")]
+fn test_swap_fails_while_paused() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.pool.pause();
+    assert!(test.pool.is_paused());
+
+    test.pool.swap(&test.user, &false, &100, &i128::MAX, &None);
+}
+
+#[test]
+fn test_unpause_resumes_swaps() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &50_000, &50_000, &50_000, &50_000);
+
+    test.pool.pause();
+    test.pool.unpause();
+    assert!(!test.pool.is_paused());
+
+    test.pool.swap(&test.user, &false, &100, &i128::MAX, &None);
+}
+
+#[test]
+fn test_rebalancing_pause_allows_only_swaps_toward_target() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 1_000_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // Skew the pool: A gets expensive
+    test.pool.swap(&test.user, &true, &20_000, &i128::MAX, &None);
+    assert!(test.pool.spot_price(&false) > SPOT_PRICE_SCALE);
+
+    test.pool.pause_rebalancing(&SPOT_PRICE_SCALE);
+    assert!(test.pool.is_paused());
+    assert_eq!(test.pool.get_rebalance_target(), Some(SPOT_PRICE_SCALE));
+
+    // Buying more A worsens the skew
+    let worsen = test.pool.try_swap(&test.user, &true, &1_000, &i128::MAX, &None);
+    assert!(worsen.is_err());
+
+    // Selling A brings its price back toward 1:1
+    test.pool.swap(&test.user, &false, &5_000, &i128::MAX, &None);
+    let price = test.pool.spot_price(&false);
+    assert!(price > SPOT_PRICE_SCALE);
+
+    // But not past it
+    let (reserve_a, reserve_b) = test.pool.get_rsrvs();
+    let overshoot = test.pool.try_swap(&test.user, &false, &(reserve_b - reserve_a), &i128::MAX, &None);
+    assert!(overshoot.is_err());
+    assert_eq!(test.pool.spot_price(&false), price);
+
+    // A full pause shuts the rebalancing window
+    test.pool.pause();
+    assert_eq!(test.pool.get_rebalance_target(), None);
+    let rebalance = test.pool.try_swap(&test.user, &false, &1_000, &i128::MAX, &None);
+    assert!(rebalance.is_err());
+}
+
+#[test]
+#[should_panic(expected = "pool is paused")]
+fn test_rebalancing_pause_keeps_deposits_closed() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 200_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.pool.pause_rebalancing(&SPOT_PRICE_SCALE);
+    test.pool.deposit(&test.user, &1_000, &0, &1_000, &0);
+}
+
+#[test]
+fn test_only_admin_can_pause_and_drain() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.env.mock_auths(&[MockAuth {
+        address: &test.user,
+        invoke: &MockAuthInvoke {
+            contract: &test.pool.address,
+            fn_name: "pause",
+            args: ().into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(test.pool.try_pause().is_err());
+
+    test.env.mock_all_auths();
+    test.pool.pause();
+
+    test.env.mock_auths(&[MockAuth {
+        address: &test.user,
+        invoke: &MockAuthInvoke {
+            contract: &test.pool.address,
+            fn_name: "emergency_withdraw",
+            args: (&test.user, &test.token_a.address).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(test
+        .pool
+        .try_emergency_withdraw(&test.user, &test.token_a.address)
+        .is_err());
+    assert_eq!(test.pool.get_rsrvs(), (100_000, 100_000));
+}
+
+#[test]
+fn test_emergency_withdraw_drains_token() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.pool.pause();
+    let recovery = Address::generate(&test.env);
+    let drained = test.pool.emergency_withdraw(&recovery, &test.token_a.address);
+    assert_eq!(test.env.auths()[0].0, test.admin);
+
+    assert_eq!(drained, 100_000);
+    assert_eq!(test.token_a.balance(&recovery), 100_000);
+    assert_eq!(test.pool.get_rsrvs(), (0, 100_000));
+
+    // LPs can still take out the other token
+    let shares = test.pool.balance_shares(&test.user);
+    let (out_a, out_b) = test.pool.withdraw(&test.user, &shares, &0, &0);
+    assert_eq!((out_a, out_b), (0, 100_000));
+}
+
+#[test]
+fn test_drained_pool_rejects_deposits_and_swaps_until_empty() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 200_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // Both sides drained, shares still outstanding
+    test.pool.pause();
+    test.pool.emergency_withdraw(&test.admin, &test.token_a.address);
+    test.pool.emergency_withdraw(&test.admin, &test.token_b.address);
+    test.pool.unpause();
+    assert_eq!(test.pool.get_rsrvs(), (0, 0));
+
+    // A deposit would otherwise be minted shares as the first one, diluted by the old shares
+    let depositor = Address::generate(&test.env);
+    test.mint_tokens(&depositor, 10_000);
+    let deposit = test.pool.try_deposit(&depositor, &10_000, &0, &10_000, &0);
+    assert!(deposit.is_err());
+    let swap = test.pool.try_swap(&depositor, &true, &1, &10_000, &None);
+    assert!(swap.is_err());
+    assert_eq!(test.token_a.balance(&depositor), 10_000);
+    assert_eq!(test.token_b.balance(&depositor), 10_000);
+
+    // Once the last LP exits the pool starts over
+    test.pool.withdraw(&test.user, &100_000, &0, &0);
+    test.pool.deposit(&depositor, &10_000, &0, &10_000, &0);
+    assert_eq!(test.pool.pool_info(&depositor), (10_000, 10_000, 10_000, 10_000));
+}
+
+#[test]
+#[should_panic(expected = "pool is drained")]
+fn test_swap_against_drained_reserve_panics() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 200_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    // With B drained, buying A would cost a single unit of B
+    test.pool.pause();
+    test.pool.emergency_withdraw(&test.admin, &test.token_b.address);
+    test.pool.unpause();
+    test.pool.swap(&test.user, &true, &50_000, &10, &None);
+}
+
+#[test]
+#[should_panic(expected = "pool is not paused")]
+fn test_emergency_withdraw_requires_pause() {
+    let test = LiquidityPoolTest::setup();
+    test.mint_tokens(&test.user, 100_000);
+    test.pool.deposit(&test.user, &100_000, &100_000, &100_000, &100_000);
+
+    test.pool.emergency_withdraw(&test.admin, &test.token_a.address);
+}
+
+#[test]
+fn test_get_reserves_normalized_mismatched_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let user = Address::generate(&env);
+
+    let token_6 = env.register(MockToken, (6u32,));
+    let token_7 = env.register(MockToken, (7u32,));
+    let (token_a, token_b) = if token_6 < token_7 {
+        (token_6.clone(), token_7.clone())
+    } else {
+        (token_7.clone(), token_6.clone())
+    };
+    let pool_id = env.register(
+        LiquidityPool,
+        (&user, &token_a, &token_b, None::<Address>, None::<Address>, 0u32),
+    );
+    let pool = crate::contract::LiquidityPoolClient::new(&env, &pool_id);
+
+    // One whole unit of each token
+    let one_6 = 1_000_000i128;
+    let one_7 = 10_000_000i128;
+    MockTokenClient::new(&env, &token_6).mint(&user, &one_6);
+    MockTokenClient::new(&env, &token_7).mint(&user, &one_7);
+
+    let (amount_a, amount_b) = if token_a == token_6 { (one_6, one_7) } else { (one_7, one_6) };
+    pool.deposit(&user, &amount_a, &amount_a, &amount_b, &amount_b);
+
+    // Raw reserves differ by 10x, normalized reserves match
+    let (raw_a, raw_b) = pool.get_rsrvs();
+    assert_ne!(raw_a, raw_b);
+    assert_eq!(pool.get_reserves_normalized(&7), (one_7, one_7));
+    assert_eq!(pool.get_reserves_normalized(&6), (one_6, one_6));
+    let one_18 = 1_000_000_000_000_000_000i128;
+    assert_eq!(pool.get_reserves_normalized(&18), (one_18, one_18));
+}
+
+const VAULT_WASM: &[u8] = include_bytes!("../../wasms/vault.wasm");
+const HOLD_STRATEGY_WASM: &[u8] = include_bytes!("../../wasms/hold_strategy.wasm");
+
+// PT/vault-share pool backed by a real vault, yield manager and tokens
+struct MaturedPtPoolTest<'a> {
+    env: Env,
+    underlying: TokenClient<'a>,
+    vault: TokenClient<'a>,
+    pt: TokenClient<'a>,
+    pool: crate::contract::LiquidityPoolClient<'a>,
+    yield_manager: Address,
+}
+
+impl<'a> MaturedPtPoolTest<'a> {
+    fn setup() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let underlying = env.register_stellar_asset_contract_v2(admin.clone()).address();
+
+        let vault = env.register(VAULT_WASM, (&underlying, 0u32));
+        let strategy = env.register(HOLD_STRATEGY_WASM, (&vault, &underlying));
+        env.invoke_contract::<()>(
+            &vault,
+            &Symbol::new(&env, "set_strategy"),
+            (&strategy,).into_val(&env),
+        );
+
+        let maturity = env.ledger().timestamp() + 1000;
+        let yield_manager = env.register(
+            YieldManager,
+            (&admin, &vault, VaultType::Vault4626, maturity, 1_000_000i128),
+        );
+        let pt = env.register(
+            PrincipalToken,
+            (
+                &yield_manager,
+                String::from_str(&env, "Principal Token"),
+                String::from_str(&env, "PT"),
+                13u32,
+                0i128,
+            ),
+        );
+        let yt = env.register(
+            YieldToken,
+            (
+                &yield_manager,
+                13u32,
+                String::from_str(&env, "Yield Token"),
+                String::from_str(&env, "YT"),
+            ),
+        );
+        env.invoke_contract::<()>(
+            &yield_manager,
+            &Symbol::new(&env, "set_token_contracts"),
+            (&pt, &yt).into_val(&env),
+        );
+
+        let (token_a, token_b) = if pt < vault { (&pt, &vault) } else { (&vault, &pt) };
+        let pool_id = env.register(
+            LiquidityPool,
+            (&admin, token_a, token_b, Some(yield_manager.clone()), None::<Address>, 0u32),
+        );
+
+        MaturedPtPoolTest {
+            underlying: TokenClient::new(&env, &underlying),
+            vault: TokenClient::new(&env, &vault),
+            pt: TokenClient::new(&env, &pt),
+            pool: crate::contract::LiquidityPoolClient::new(&env, &pool_id),
+            yield_manager,
+            env,
+        }
+    }
+
+    // Mints underlying, deposits it in the vault and returns the shares
+    fn vault_shares(&self, user: &Address, assets: i128) -> i128 {
+        StellarAssetClient::new(&self.env, &self.underlying.address).mint(user, &assets);
+        self.env.invoke_contract(
+            &self.vault.address,
+            &Symbol::new(&self.env, "deposit"),
+            (assets, user, user, user).into_val(&self.env),
+        )
+    }
+
+    // Adds liquidity in the pool's token order
+    fn add_liquidity(&self, lp: &Address, pt_amount: i128, share_amount: i128) {
+        let (amount_a, amount_b) = if self.pt.address < self.vault.address {
+            (pt_amount, share_amount)
+        } else {
+            (share_amount, pt_amount)
+        };
+        self.pool.deposit(lp, &amount_a, &amount_a, &amount_b, &amount_b);
+    }
+}
+
+#[test]
+fn test_exit_matured_pt_pool_delivers_underlying() {
+    let test = MaturedPtPoolTest::setup();
+    let minter = Address::generate(&test.env);
+    let lp = Address::generate(&test.env);
+
+    // The minter splits shares into PT/YT and hands the LP its PT and some shares
+    let shares = test.vault_shares(&minter, 20_000_000);
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&minter, shares / 2, 0i128, 0i128).into_val(&test.env),
+    );
+    test.pt.transfer(&minter, &lp, &pt_minted);
+    test.vault.transfer(&minter, &lp, &(shares / 2));
+
+    test.add_liquidity(&lp, pt_minted, shares / 2);
+    let lp_shares = test.pool.balance_shares(&lp);
+
+    test.env.ledger().with_mut(|li| li.timestamp += 1100);
+
+    let underlying_out = test.pool.exit_matured_pt_pool(&lp, &lp_shares, &20_000_000);
+
+    // Only underlying is left: both legs were worth shares / 2 each
+    assert_eq!(underlying_out, 20_000_000);
+    assert_eq!(test.underlying.balance(&lp), underlying_out);
+    assert_eq!(test.pt.balance(&lp), 0);
+    assert_eq!(test.vault.balance(&lp), 0);
+    assert_eq!(test.pool.balance_shares(&lp), 0);
+    assert_eq!(test.pool.get_rsrvs(), (0, 0));
+}
+
+// Sets up an LP in the PT/share pool and moves past maturity, returning the LP
+fn matured_pool_lp(test: &MaturedPtPoolTest) -> Address {
+    let lp = Address::generate(&test.env);
+    let shares = test.vault_shares(&lp, 20_000_000);
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&lp, shares / 2, 0i128, 0i128).into_val(&test.env),
+    );
+    test.add_liquidity(&lp, pt_minted, shares / 2);
+    test.env.ledger().with_mut(|li| li.timestamp += 1100);
+    lp
+}
+
+#[test]
+#[should_panic(expected = "min not satisfied")]
+fn test_exit_matured_pt_pool_below_min_underlying() {
+    let test = MaturedPtPoolTest::setup();
+    let lp = matured_pool_lp(&test);
+    let lp_shares = test.pool.balance_shares(&lp);
+
+    // The full exit delivers 20_000_000 underlying
+    test.pool.exit_matured_pt_pool(&lp, &lp_shares, &20_000_001);
+}
+
+#[test]
+fn test_exit_matured_pt_pool_returns_pt_dust() {
+    let test = MaturedPtPoolTest::setup();
+    let lp = matured_pool_lp(&test);
+    let lp_shares = test.pool.balance_shares(&lp);
+
+    // A third of the pool's PT isn't a whole number of shares
+    let underlying_out = test.pool.exit_matured_pt_pool(&lp, &(lp_shares / 3), &0);
+
+    let pt_dust = test.pt.balance(&lp);
+    assert!(pt_dust > 0 && pt_dust < 1_000_000);
+    assert_eq!(test.vault.balance(&lp), 0);
+    assert_eq!(test.underlying.balance(&lp), underlying_out);
+    assert!(underlying_out > 0);
+}
+
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_exit_matured_pt_pool_zero_shares() {
+    let test = MaturedPtPoolTest::setup();
+    let lp = matured_pool_lp(&test);
+    test.pool.exit_matured_pt_pool(&lp, &0, &0);
+}
+
+#[test]
+#[should_panic(expected = "share_amount must be strictly positive")]
+fn test_exit_matured_pt_pool_negative_shares() {
+    let test = MaturedPtPoolTest::setup();
+    let lp = matured_pool_lp(&test);
+    test.pool.exit_matured_pt_pool(&lp, &-1, &0);
+}
+
+#[test]
+#[should_panic(expected = "Maturity not reached")]
+fn test_exit_matured_pt_pool_before_maturity() {
+    let test = MaturedPtPoolTest::setup();
+    let lp = Address::generate(&test.env);
+
+    let shares = test.vault_shares(&lp, 20_000_000);
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&lp, shares / 2, 0i128, 0i128).into_val(&test.env),
+    );
+    test.add_liquidity(&lp, pt_minted, shares / 2);
+
+    let lp_shares = test.pool.balance_shares(&lp);
+    test.pool.exit_matured_pt_pool(&lp, &lp_shares, &0);
+}
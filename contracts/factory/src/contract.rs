@@ -1,17 +1,65 @@
-use soroban_sdk::{Address, BytesN, Env, String};
+use soroban_sdk::{token, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec};
+use crate::events::{PoolsDeployed, Rollover, YieldManagerDeployed};
 use crate::storage;
-use yield_manager_interface::YieldManagerClient;
+use yield_manager_interface::{VaultType, YieldManagerClient};
 
 #[cfg(feature = "contract")]
 use soroban_sdk::{contract, contractimpl};
 
-const PT_WASM_HASH: [u8; 32] = [0u8; 32];
-const YT_WASM_HASH: [u8; 32] = [0u8; 32];
-const YM_WASM_HASH: [u8; 32] = [0u8; 32];
-const AMM_WASM_HASH: [u8; 32] = [0u8; 32];
+// Precision of the yield manager's rate, which quotes the assets behind 10^RATE_DECIMALS shares.
+// PT/YT carry the vault asset's decimals plus these
+const RATE_DECIMALS: u32 = 6;
+
+// Salt tags distinguishing the contracts deployed within one epoch
+const YM_SALT_TAG: u8 = 0;
+const PT_SALT_TAG: u8 = 1;
+const YT_SALT_TAG: u8 = 2;
+const PT_POOL_SALT_TAG: u8 = 3;
+const YT_POOL_SALT_TAG: u8 = 4;
+const PERMISSIONLESS_YM_SALT_TAG: u8 = 5;
+const PERMISSIONLESS_PT_SALT_TAG: u8 = 6;
+const PERMISSIONLESS_YT_SALT_TAG: u8 = 7;
+
+// Salt tags and token name prefixes of one numbered series of deployments. The admin's
+// epochs and the permissionless deploys are counted separately, so a permissionless deploy
+// never moves the admin's epoch, and their own tags keep the two series' salts apart
+struct Series {
+    salt_tags: [u8; 3],
+    pt_name: &'static str,
+    pt_symbol: &'static str,
+    yt_name: &'static str,
+    yt_symbol: &'static str,
+}
+
+const EPOCH_SERIES: Series = Series {
+    salt_tags: [YM_SALT_TAG, PT_SALT_TAG, YT_SALT_TAG],
+    pt_name: "Principal Token #",
+    pt_symbol: "PT-",
+    yt_name: "Yield Token #",
+    yt_symbol: "YT-",
+};
+
+const PERMISSIONLESS_SERIES: Series = Series {
+    salt_tags: [
+        PERMISSIONLESS_YM_SALT_TAG,
+        PERMISSIONLESS_PT_SALT_TAG,
+        PERMISSIONLESS_YT_SALT_TAG,
+    ],
+    pt_name: "Principal Token #P",
+    pt_symbol: "PT-P",
+    yt_name: "Yield Token #P",
+    yt_symbol: "YT-P",
+};
 
 pub trait FactoryTrait {
-    fn __constructor(env: Env, admin: Address);
+    fn __constructor(
+        env: Env,
+        admin: Address,
+        pt_wasm_hash: BytesN<32>,
+        yt_wasm_hash: BytesN<32>,
+        ym_wasm_hash: BytesN<32>,
+        amm_wasm_hash: BytesN<32>,
+    );
 
     fn deploy_yield_manager(
         env: Env,
@@ -26,6 +74,29 @@ pub trait FactoryTrait {
         vault_share_token: Address,
     ) -> (Address, Address);
 
+    // Fee charged to anyone deploying a yield manager without the admin
+    fn set_deploy_fee(env: Env, fee_token: Address, amount: i128);
+    fn get_deploy_fee(env: Env) -> Option<(Address, i128)>;
+
+    // Deploys a yield manager for anyone paying the deploy fee, which goes to the admin.
+    // It is only recorded in the permissionless registry: the current contracts and the
+    // history rollover and migration rely on stay under the admin's control
+    // (deploy_yield_manager_permissionless exceeds the 32 character function name limit)
+    fn deploy_ym_permissionless(
+        env: Env,
+        vault: Address,
+        maturity: u64,
+        payer: Address,
+    ) -> Address;
+
+    // Deploys a yield manager, PT, YT and both pools in one call
+    // Returns (yield_manager, pt, yt, pt_pool, yt_pool)
+    fn deploy_full_set(
+        env: Env,
+        vault: Address,
+        maturity: u64,
+    ) -> (Address, Address, Address, Address, Address);
+
     // Getter functions for current contracts
     fn get_current_yield_manager(env: Env) -> Option<Address>;
     fn get_current_pt_token(env: Env) -> Option<Address>;
@@ -33,8 +104,32 @@ pub trait FactoryTrait {
     fn get_current_pt_pool(env: Env) -> Option<Address>;
     fn get_current_yt_pool(env: Env) -> Option<Address>;
 
+    // All current contracts in one call
+    // Returns (yield_manager, pt, yt, pt_pool, yt_pool)
+    #[allow(clippy::type_complexity)]
+    fn current_epoch(
+        env: Env,
+    ) -> (
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+    );
+
+    // Historical admin-deployed yield managers and their maturities, oldest first
+    fn get_yield_manager_at(env: Env, index: u32) -> Option<(Address, u64)>;
+    fn get_all_yield_managers(env: Env) -> Vec<(Address, u64)>;
+
+    // Yield managers deployed through deploy_ym_permissionless and their maturities, oldest first
+    fn get_permissionless_deployments(env: Env) -> Vec<(Address, u64)>;
+
     // Rollover function to deploy new contracts after maturity
     fn rollover_if_expired(env: Env, new_maturity: u64) -> bool;
+
+    // Redeems the user's PT from the matured previous epoch and deposits the vault shares
+    // into the current one. Returns the (pt, yt) minted by the current yield manager
+    fn migrate_position(env: Env, user: Address) -> (i128, i128);
 }
 
 #[cfg(feature = "contract")]
@@ -42,45 +137,63 @@ pub trait FactoryTrait {
 pub struct Factory;
 
 #[cfg(feature = "contract")]
-#[contractimpl]
-impl FactoryTrait for Factory {
-    fn __constructor(env: Env, admin: Address) {
-        storage::set_admin(&env, &admin);
+impl Factory {
+    // Deploys a yield manager with its PT and YT and records them as the current epoch
+    fn deploy_set(env: &Env, vault: Address, vault_type: VaultType, maturity: u64) -> Address {
+        // Each deployment starts a new epoch so salts never repeat
+        let epoch = storage::get_epoch(env) + 1;
+        storage::set_epoch(env, epoch);
+
+        let (ym_addr, pt_addr, yt_addr) =
+            Self::deploy_contracts(env, vault, vault_type, maturity, epoch, &EPOCH_SERIES);
+
+        // Store current contracts in factory storage
+        storage::set_current_yield_manager(env, &ym_addr);
+        storage::set_current_pt_token(env, &pt_addr);
+        storage::set_current_yt_token(env, &yt_addr);
+        storage::push_yield_manager_history(env, &ym_addr, maturity);
+
+        ym_addr
     }
 
-    fn deploy_yield_manager(
-        env: Env,
+    // Deploys a yield manager with its PT and YT without recording them anywhere, as
+    // deployment `number` of `series`, which the caller has counted
+    // Returns (yield_manager, pt, yt)
+    fn deploy_contracts(
+        env: &Env,
         vault: Address,
+        vault_type: VaultType,
         maturity: u64,
-    ) -> Address {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
-
-        // Create WASM hash BytesN from constants
-        let pt_wasm_hash = BytesN::from_array(&env, &PT_WASM_HASH);
-        let yt_wasm_hash = BytesN::from_array(&env, &YT_WASM_HASH);
-        let ym_wasm_hash = BytesN::from_array(&env, &YM_WASM_HASH);
+        number: u32,
+        series: &Series,
+    ) -> (Address, Address, Address) {
+        let pt_wasm_hash = storage::get_pt_wasm_hash(env);
+        let yt_wasm_hash = storage::get_yt_wasm_hash(env);
+        let ym_wasm_hash = storage::get_ym_wasm_hash(env);
+        let [ym_tag, pt_tag, yt_tag] = series.salt_tags;
 
         // Deploy yield manager first
-        // Use a unique salt based on vault address and maturity
-        let ym_salt_data = [0u8; 32];
-        // Simple salt derivation - could be made more sophisticated
-        let ym_salt = BytesN::from_array(&env, &ym_salt_data);
+        let ym_salt = Self::salt(env, number, &vault, maturity, ym_tag);
 
         let ym_addr = env
             .deployer()
-            .with_current_contract(ym_salt.clone())
+            .with_current_contract(ym_salt)
             .deploy_v2(
                 ym_wasm_hash,
                 (
                     env.current_contract_address(),
-                    vault,
+                    vault.clone(),
+                    vault_type,
                     maturity,
+                    10i128.pow(RATE_DECIMALS),
                 ),
             );
+        let ym_client = YieldManagerClient::new(env, &ym_addr);
+        let asset_decimals = token::Client::new(env, &ym_client.get_underlying_asset()).decimals();
+        let token_decimals = asset_decimals + RATE_DECIMALS;
 
-        // Deploy Principal Token with yield manager as admin
-        let pt_salt = BytesN::from_array(&env, &[0u8; 32]);
+        // Deploy Principal Token with yield manager as admin (uncapped)
+        let pt_salt = Self::salt(env, number, &vault, maturity, pt_tag);
         let pt_addr = env
             .deployer()
             .with_current_contract(pt_salt)
@@ -88,13 +201,15 @@ impl FactoryTrait for Factory {
                 pt_wasm_hash,
                 (
                     ym_addr.clone(),
-                    String::from_str(&env, "Principal Token"),
-                    String::from_str(&env, "PT"),
+                    Self::epoch_string(env, series.pt_name, number),
+                    Self::epoch_string(env, series.pt_symbol, number),
+                    token_decimals,
+                    0i128,
                 ),
             );
 
         // Deploy Yield Token with yield manager as admin
-        let yt_salt = BytesN::from_array(&env, &[1u8; 32]);
+        let yt_salt = Self::salt(env, number, &vault, maturity, yt_tag);
         let yt_addr = env
             .deployer()
             .with_current_contract(yt_salt)
@@ -102,61 +217,225 @@ impl FactoryTrait for Factory {
                 yt_wasm_hash,
                 (
                     ym_addr.clone(),
-                    String::from_str(&env, "Yield Token"),
-                    String::from_str(&env, "YT"),
+                    token_decimals,
+                    Self::epoch_string(env, series.yt_name, number),
+                    Self::epoch_string(env, series.yt_symbol, number),
                 ),
             );
 
         // Set token contracts in yield manager
-        let ym_client = YieldManagerClient::new(&env, &ym_addr);
         ym_client.set_token_contracts(&pt_addr, &yt_addr);
 
-        // Store current contracts in factory storage
-        storage::set_current_yield_manager(&env, &ym_addr);
-        storage::set_current_pt_token(&env, &pt_addr);
-        storage::set_current_yt_token(&env, &yt_addr);
+        YieldManagerDeployed {
+            vault,
+            ym: ym_addr.clone(),
+            pt: pt_addr.clone(),
+            yt: yt_addr.clone(),
+            maturity,
+        }
+        .publish(env);
 
-        ym_addr
+        (ym_addr, pt_addr, yt_addr)
     }
 
-    fn deploy_liquidity_pools(
-        env: Env,
+    // Deploys the PT and YT pools against the vault share and records them as current
+    fn deploy_pools(
+        env: &Env,
         pt_token: Address,
         yt_token: Address,
         vault_share_token: Address,
     ) -> (Address, Address) {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        let amm_wasm_hash = storage::get_amm_wasm_hash(env);
 
-        let amm_wasm_hash = BytesN::from_array(&env, &AMM_WASM_HASH);
+        // Pools settle against the yield manager's maturity
+        let yield_manager = storage::get_current_yield_manager(env);
+        let maturity = yield_manager
+            .as_ref()
+            .map(|ym| YieldManagerClient::new(env, ym).get_maturity())
+            .unwrap_or(0);
+        let epoch = storage::get_epoch(env);
+
+        // The factory admin holds the pools' emergency controls
+        let admin = storage::get_admin(env);
+
+        // Pools require token_a < token_b
+        let sorted = |x: Address, y: Address| if x < y { (x, y) } else { (y, x) };
 
         // Deploy PT/Vault Share AMM pool
-        let pt_pool_salt = BytesN::from_array(&env, &[2u8; 32]);
+        let (pt_pool_a, pt_pool_b) = sorted(pt_token, vault_share_token.clone());
+        let pt_pool_salt = Self::salt(env, epoch, &vault_share_token, maturity, PT_POOL_SALT_TAG);
         let pt_pool_addr = env
             .deployer()
             .with_current_contract(pt_pool_salt)
             .deploy_v2(
                 amm_wasm_hash.clone(),
-                (pt_token, vault_share_token.clone()),
+                (
+                    admin.clone(),
+                    pt_pool_a,
+                    pt_pool_b,
+                    yield_manager.clone(),
+                    None::<Address>,
+                    0u32,
+                ),
             );
 
         // Deploy YT/Vault Share AMM pool
-        let yt_pool_salt = BytesN::from_array(&env, &[3u8; 32]);
+        let yt_pool_salt = Self::salt(env, epoch, &vault_share_token, maturity, YT_POOL_SALT_TAG);
+        let (yt_pool_a, yt_pool_b) = sorted(yt_token, vault_share_token);
         let yt_pool_addr = env
             .deployer()
             .with_current_contract(yt_pool_salt)
             .deploy_v2(
                 amm_wasm_hash,
-                (yt_token, vault_share_token),
+                (admin, yt_pool_a, yt_pool_b, yield_manager, None::<Address>, 0u32),
             );
 
         // Store current pool addresses in factory storage
-        storage::set_current_pt_pool(&env, &pt_pool_addr);
-        storage::set_current_yt_pool(&env, &yt_pool_addr);
+        storage::set_current_pt_pool(env, &pt_pool_addr);
+        storage::set_current_yt_pool(env, &yt_pool_addr);
+
+        PoolsDeployed {
+            pt_pool: pt_pool_addr.clone(),
+            yt_pool: yt_pool_addr.clone(),
+        }
+        .publish(env);
 
         (pt_pool_addr, yt_pool_addr)
     }
 
+    // `prefix` followed by the deployment number in decimal, e.g. "PT-3", so each
+    // deployment's tokens are told apart in wallets
+    fn epoch_string(env: &Env, prefix: &str, epoch: u32) -> String {
+        let mut data = Bytes::from_slice(env, prefix.as_bytes());
+        Self::push_decimal(&mut data, epoch);
+        data.to_string()
+    }
+
+    // Appends `n` in decimal, most significant digit first
+    fn push_decimal(data: &mut Bytes, n: u32) {
+        if n >= 10 {
+            Self::push_decimal(data, n / 10);
+        }
+        data.push_back(b'0' + (n % 10) as u8);
+    }
+
+    // Derives a deployment salt unique to the deployment number, vault, maturity and
+    // contract role (the tag, which also tells the two series apart)
+    fn salt(env: &Env, number: u32, vault: &Address, maturity: u64, tag: u8) -> BytesN<32> {
+        let mut data = Bytes::new(env);
+        data.extend_from_array(&number.to_be_bytes());
+        data.append(&vault.clone().to_xdr(env));
+        data.extend_from_array(&maturity.to_be_bytes());
+        data.push_back(tag);
+        env.crypto().sha256(&data).into()
+    }
+}
+
+#[cfg(feature = "contract")]
+#[contractimpl]
+impl FactoryTrait for Factory {
+    fn __constructor(
+        env: Env,
+        admin: Address,
+        pt_wasm_hash: BytesN<32>,
+        yt_wasm_hash: BytesN<32>,
+        ym_wasm_hash: BytesN<32>,
+        amm_wasm_hash: BytesN<32>,
+    ) {
+        storage::set_admin(&env, &admin);
+        storage::set_wasm_hashes(&env, &pt_wasm_hash, &yt_wasm_hash, &ym_wasm_hash, &amm_wasm_hash);
+    }
+
+    fn deploy_yield_manager(
+        env: Env,
+        vault: Address,
+        maturity: u64,
+    ) -> Address {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        Self::deploy_set(&env, vault, VaultType::Vault4626, maturity)
+    }
+
+    fn deploy_liquidity_pools(
+        env: Env,
+        pt_token: Address,
+        yt_token: Address,
+        vault_share_token: Address,
+    ) -> (Address, Address) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        Self::deploy_pools(&env, pt_token, yt_token, vault_share_token)
+    }
+
+    fn set_deploy_fee(env: Env, fee_token: Address, amount: i128) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        if amount < 0 {
+            panic!("Fee must not be negative");
+        }
+
+        storage::set_deploy_fee(&env, &fee_token, amount);
+    }
+
+    fn get_deploy_fee(env: Env) -> Option<(Address, i128)> {
+        storage::get_deploy_fee(&env)
+    }
+
+    fn deploy_ym_permissionless(
+        env: Env,
+        vault: Address,
+        maturity: u64,
+        payer: Address,
+    ) -> Address {
+        payer.require_auth();
+
+        let (fee_token, amount) =
+            storage::get_deploy_fee(&env).expect("Permissionless deploys not enabled");
+
+        // The admin is the treasury, the transfer reverts the deploy if the payer can't cover it
+        if amount > 0 {
+            let admin = storage::get_admin(&env);
+            token::Client::new(&env, &fee_token).transfer(&payer, &admin, &amount);
+        }
+
+        // Numbered by the permissionless registry, the admin's epoch is left alone
+        let number = storage::get_permissionless_deployments(&env).len() + 1;
+        let (ym_addr, _, _) = Self::deploy_contracts(
+            &env,
+            vault,
+            VaultType::Vault4626,
+            maturity,
+            number,
+            &PERMISSIONLESS_SERIES,
+        );
+        storage::push_permissionless_deployment(&env, &ym_addr, maturity);
+
+        ym_addr
+    }
+
+    fn deploy_full_set(
+        env: Env,
+        vault: Address,
+        maturity: u64,
+    ) -> (Address, Address, Address, Address, Address) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        let ym_addr = Self::deploy_set(&env, vault, VaultType::Vault4626, maturity);
+        let ym_client = YieldManagerClient::new(&env, &ym_addr);
+        let (pt_addr, yt_addr) = ym_client.get_token_contracts();
+
+        // The vault address is the vault share token
+        let vault_share_token = ym_client.get_vault();
+        let (pt_pool_addr, yt_pool_addr) =
+            Self::deploy_pools(&env, pt_addr.clone(), yt_addr.clone(), vault_share_token);
+
+        (ym_addr, pt_addr, yt_addr, pt_pool_addr, yt_pool_addr)
+    }
+
     // Getter functions for current contracts
     fn get_current_yield_manager(env: Env) -> Option<Address> {
         storage::get_current_yield_manager(&env)
@@ -178,6 +457,37 @@ impl FactoryTrait for Factory {
         storage::get_current_yt_pool(&env)
     }
 
+    #[allow(clippy::type_complexity)]
+    fn current_epoch(
+        env: Env,
+    ) -> (
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+        Option<Address>,
+    ) {
+        (
+            storage::get_current_yield_manager(&env),
+            storage::get_current_pt_token(&env),
+            storage::get_current_yt_token(&env),
+            storage::get_current_pt_pool(&env),
+            storage::get_current_yt_pool(&env),
+        )
+    }
+
+    fn get_yield_manager_at(env: Env, index: u32) -> Option<(Address, u64)> {
+        storage::get_yield_manager_history(&env).get(index)
+    }
+
+    fn get_all_yield_managers(env: Env) -> Vec<(Address, u64)> {
+        storage::get_yield_manager_history(&env)
+    }
+
+    fn get_permissionless_deployments(env: Env) -> Vec<(Address, u64)> {
+        storage::get_permissionless_deployments(&env)
+    }
+
     /// Checks if current yield manager has expired and deploys new contracts if so
     /// Returns true if rollover occurred, false otherwise
     fn rollover_if_expired(env: Env, new_maturity: u64) -> bool {
@@ -198,11 +508,15 @@ impl FactoryTrait for Factory {
         }
 
         // Maturity has expired, deploy new contracts
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
         let vault = ym_client.get_vault();
+        let vault_type = ym_client.get_vault_type();
 
-        // Deploy new yield manager with new maturity
+        // Deploy new yield manager with new maturity and the same vault type
         // This sets new yt/pt tokens in storage
-        let new_ym_addr = Self::deploy_yield_manager(env.clone(), vault.clone(), new_maturity);
+        let new_ym = Self::deploy_set(&env, vault.clone(), vault_type, new_maturity);
 
         // Get the newly deployed token addresses from storage
         let new_pt_addr = storage::get_current_pt_token(&env).unwrap();
@@ -210,13 +524,49 @@ impl FactoryTrait for Factory {
 
         // Deploy new liquidity pools
         // Vault address is the vault share token
-        Self::deploy_liquidity_pools(
-            env,
+        Self::deploy_pools(
+            &env,
             new_pt_addr,
             new_yt_addr,
             vault,
         );
 
+        Rollover {
+            old_ym: current_ym,
+            new_ym,
+            new_maturity,
+        }
+        .publish(&env);
+
         true
     }
+
+    fn migrate_position(env: Env, user: Address) -> (i128, i128) {
+        // The user's authorization also covers the redeem and deposit made on their behalf
+        user.require_auth();
+
+        let history = storage::get_yield_manager_history(&env);
+        if history.len() < 2 {
+            panic!("No previous epoch to migrate from");
+        }
+        let (old_ym, _) = history.get(history.len() - 2).unwrap();
+        let (new_ym, _) = history.get(history.len() - 1).unwrap();
+
+        let old_ym_client = YieldManagerClient::new(&env, &old_ym);
+        let new_ym_client = YieldManagerClient::new(&env, &new_ym);
+        if !old_ym_client.is_matured() {
+            panic!("Previous epoch not matured");
+        }
+        if old_ym_client.get_vault() != new_ym_client.get_vault() {
+            panic!("Epochs wrap different vaults");
+        }
+
+        // Old PT to vault shares held by the user, then straight into the new epoch
+        let shares = old_ym_client.redeem_all(&user);
+        if shares == 0 {
+            panic!("Nothing to migrate");
+        }
+
+        new_ym_client.deposit(&user, &shares, &0, &0)
+    }
 }
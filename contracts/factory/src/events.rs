@@ -0,0 +1,27 @@
+use soroban_sdk::{contractevent, Address};
+
+// Published when a yield manager and its PT/YT are deployed for a vault
+#[contractevent(topics = ["ym_deployed"])]
+pub struct YieldManagerDeployed {
+    #[topic]
+    pub vault: Address,
+    pub ym: Address,
+    pub pt: Address,
+    pub yt: Address,
+    pub maturity: u64,
+}
+
+// Published when the PT and YT pools for the current epoch are deployed
+#[contractevent(topics = ["pools_deployed"])]
+pub struct PoolsDeployed {
+    pub pt_pool: Address,
+    pub yt_pool: Address,
+}
+
+// Published when an expired yield manager is replaced by a new epoch
+#[contractevent(topics = ["rollover"])]
+pub struct Rollover {
+    pub old_ym: Address,
+    pub new_ym: Address,
+    pub new_maturity: u64,
+}
@@ -2,5 +2,9 @@
 
 mod storage;
 mod contract;
+mod events;
 
 pub use contract::{Factory, FactoryTrait};
+
+#[cfg(test)]
+mod test;
@@ -1,4 +1,4 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{Address, BytesN, Env, Vec};
 
 // Storage keys
 const ADMIN_KEY: &str = "admin";
@@ -7,6 +7,14 @@ const CURRENT_PT_TOKEN_KEY: &str = "cur_pt";
 const CURRENT_YT_TOKEN_KEY: &str = "cur_yt";
 const CURRENT_PT_POOL_KEY: &str = "cur_pt_pool";
 const CURRENT_YT_POOL_KEY: &str = "cur_yt_pool";
+const PT_WASM_HASH_KEY: &str = "pt_wasm";
+const YT_WASM_HASH_KEY: &str = "yt_wasm";
+const YM_WASM_HASH_KEY: &str = "ym_wasm";
+const AMM_WASM_HASH_KEY: &str = "amm_wasm";
+const EPOCH_KEY: &str = "epoch";
+const YIELD_MANAGER_HISTORY_KEY: &str = "ym_history";
+const DEPLOY_FEE_KEY: &str = "deploy_fee";
+const PERMISSIONLESS_DEPLOYMENTS_KEY: &str = "perm_yms";
 
 // Admin functions
 pub fn set_admin(env: &Env, admin: &Address) {
@@ -20,6 +28,52 @@ pub fn get_admin(env: &Env) -> Address {
         .expect("Admin not set")
 }
 
+// Fee token and amount charged for permissionless deploys, unset until the admin configures it
+pub fn set_deploy_fee(env: &Env, fee_token: &Address, amount: i128) {
+    env.storage().instance().set(&DEPLOY_FEE_KEY, &(fee_token.clone(), amount));
+}
+
+pub fn get_deploy_fee(env: &Env) -> Option<(Address, i128)> {
+    env.storage().instance().get(&DEPLOY_FEE_KEY)
+}
+
+// Deployment epoch, incremented for every yield manager the admin deploys
+pub fn set_epoch(env: &Env, epoch: u32) {
+    env.storage().instance().set(&EPOCH_KEY, &epoch);
+}
+
+pub fn get_epoch(env: &Env) -> u32 {
+    env.storage().instance().get(&EPOCH_KEY).unwrap_or(0)
+}
+
+// Every yield manager the admin deployed, with its maturity, in deployment order
+pub fn push_yield_manager_history(env: &Env, yield_manager: &Address, maturity: u64) {
+    let mut history = get_yield_manager_history(env);
+    history.push_back((yield_manager.clone(), maturity));
+    env.storage().instance().set(&YIELD_MANAGER_HISTORY_KEY, &history);
+}
+
+pub fn get_yield_manager_history(env: &Env) -> Vec<(Address, u64)> {
+    env.storage()
+        .instance()
+        .get(&YIELD_MANAGER_HISTORY_KEY)
+        .unwrap_or(Vec::new(env))
+}
+
+// Yield managers deployed through the permissionless path, kept apart from the admin's history
+pub fn push_permissionless_deployment(env: &Env, yield_manager: &Address, maturity: u64) {
+    let mut deployments = get_permissionless_deployments(env);
+    deployments.push_back((yield_manager.clone(), maturity));
+    env.storage().instance().set(&PERMISSIONLESS_DEPLOYMENTS_KEY, &deployments);
+}
+
+pub fn get_permissionless_deployments(env: &Env) -> Vec<(Address, u64)> {
+    env.storage()
+        .instance()
+        .get(&PERMISSIONLESS_DEPLOYMENTS_KEY)
+        .unwrap_or(Vec::new(env))
+}
+
 // Current yield manager
 pub fn set_current_yield_manager(env: &Env, yield_manager: &Address) {
     env.storage().instance().set(&CURRENT_YIELD_MANAGER_KEY, yield_manager);
@@ -64,3 +118,45 @@ pub fn set_current_yt_pool(env: &Env, yt_pool: &Address) {
 pub fn get_current_yt_pool(env: &Env) -> Option<Address> {
     env.storage().instance().get(&CURRENT_YT_POOL_KEY)
 }
+
+// WASM hashes of the deployed components (set once at construction)
+pub fn set_wasm_hashes(
+    env: &Env,
+    pt_wasm_hash: &BytesN<32>,
+    yt_wasm_hash: &BytesN<32>,
+    ym_wasm_hash: &BytesN<32>,
+    amm_wasm_hash: &BytesN<32>,
+) {
+    env.storage().instance().set(&PT_WASM_HASH_KEY, pt_wasm_hash);
+    env.storage().instance().set(&YT_WASM_HASH_KEY, yt_wasm_hash);
+    env.storage().instance().set(&YM_WASM_HASH_KEY, ym_wasm_hash);
+    env.storage().instance().set(&AMM_WASM_HASH_KEY, amm_wasm_hash);
+}
+
+pub fn get_pt_wasm_hash(env: &Env) -> BytesN<32> {
+    env.storage()
+        .instance()
+        .get(&PT_WASM_HASH_KEY)
+        .expect("PT wasm hash not set")
+}
+
+pub fn get_yt_wasm_hash(env: &Env) -> BytesN<32> {
+    env.storage()
+        .instance()
+        .get(&YT_WASM_HASH_KEY)
+        .expect("YT wasm hash not set")
+}
+
+pub fn get_ym_wasm_hash(env: &Env) -> BytesN<32> {
+    env.storage()
+        .instance()
+        .get(&YM_WASM_HASH_KEY)
+        .expect("YM wasm hash not set")
+}
+
+pub fn get_amm_wasm_hash(env: &Env) -> BytesN<32> {
+    env.storage()
+        .instance()
+        .get(&AMM_WASM_HASH_KEY)
+        .expect("AMM wasm hash not set")
+}
@@ -0,0 +1,586 @@
+#![cfg(test)]
+use crate::contract::{Factory, FactoryClient};
+use crate::events::{PoolsDeployed, Rollover, YieldManagerDeployed};
+use soroban_sdk::{
+    events::Event,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, BytesN, Env, IntoVal, String, Symbol, Val, Vec,
+};
+use yield_manager_interface::{VaultType, YieldManagerClient};
+
+const VAULT_WASM: &[u8] = include_bytes!("../../../wasms/vault.wasm");
+const HOLD_STRATEGY_WASM: &[u8] = include_bytes!("../../../wasms/hold_strategy.wasm");
+const PT_WASM: &[u8] = include_bytes!("../../../wasms/principal_token.wasm");
+const YT_WASM: &[u8] = include_bytes!("../../../wasms/yield_token.wasm");
+const YM_WASM: &[u8] = include_bytes!("../../../wasms/yield_manager.wasm");
+const AMM_WASM: &[u8] = include_bytes!("../../../wasms/amm.wasm");
+
+struct FactoryTest {
+    env: Env,
+    admin: Address,
+    underlying_asset_addr: Address,
+    vault_addr: Address,
+    factory: Address,
+}
+
+impl FactoryTest {
+    fn setup() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+
+        // Create underlying asset
+        let underlying_admin = Address::generate(&env);
+        let underlying_asset_addr = env
+            .register_stellar_asset_contract_v2(underlying_admin)
+            .address();
+
+        // Deploy vault and hold strategy from WASM
+        let vault_addr = env.register(VAULT_WASM, (&underlying_asset_addr, 0u32));
+        let strategy_addr = env.register(HOLD_STRATEGY_WASM, (&vault_addr, &underlying_asset_addr));
+        env.invoke_contract::<()>(
+            &vault_addr,
+            &Symbol::new(&env, "set_strategy"),
+            (&strategy_addr,).into_val(&env),
+        );
+
+        // Upload component WASMs and hand their hashes to the factory
+        let pt_wasm_hash: BytesN<32> = env.deployer().upload_contract_wasm(PT_WASM);
+        let yt_wasm_hash: BytesN<32> = env.deployer().upload_contract_wasm(YT_WASM);
+        let ym_wasm_hash: BytesN<32> = env.deployer().upload_contract_wasm(YM_WASM);
+        let amm_wasm_hash: BytesN<32> = env.deployer().upload_contract_wasm(AMM_WASM);
+
+        let factory = env.register(
+            Factory,
+            (&admin, pt_wasm_hash, yt_wasm_hash, ym_wasm_hash, amm_wasm_hash),
+        );
+
+        FactoryTest {
+            env,
+            admin,
+            underlying_asset_addr,
+            vault_addr,
+            factory,
+        }
+    }
+
+    fn client(&self) -> FactoryClient<'_> {
+        FactoryClient::new(&self.env, &self.factory)
+    }
+
+    // Asserts `events` contain `expected` published by the factory
+    fn assert_factory_event(&self, events: &Vec<(Address, Vec<Val>, Val)>, expected: &impl Event) {
+        let published = events
+            .iter()
+            .filter(|(contract, _, _)| *contract == self.factory)
+            .any(|event| {
+                vec![&self.env, event]
+                    == vec![
+                        &self.env,
+                        (
+                            self.factory.clone(),
+                            expected.topics(&self.env),
+                            expected.data(&self.env),
+                        ),
+                    ]
+            });
+        assert!(published, "factory event not published");
+    }
+}
+
+#[test]
+fn test_deploy_yield_manager_links_tokens() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    let ym_addr = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let ym = YieldManagerClient::new(&test.env, &ym_addr);
+
+    assert_eq!(client.get_current_yield_manager(), Some(ym_addr.clone()));
+    assert_eq!(ym.get_vault(), test.vault_addr);
+    assert_eq!(ym.get_vault_type(), VaultType::Vault4626);
+    assert_eq!(ym.get_maturity(), maturity);
+
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+    assert_eq!(ym.get_token_contracts(), (pt.clone(), yt.clone()));
+    // The 7 decimal asset plus the 1e6 rate precision
+    assert_eq!(TokenClient::new(&test.env, &pt).decimals(), 13);
+    assert_eq!(TokenClient::new(&test.env, &yt).decimals(), 13);
+}
+
+#[test]
+fn test_deployed_set_accepts_deposits() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let user = Address::generate(&test.env);
+
+    let ym_addr = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let ym = YieldManagerClient::new(&test.env, &ym_addr);
+
+    StellarAssetClient::new(&test.env, &test.underlying_asset_addr).mint(&user, &1_000_000);
+    let shares: i128 = test.env.invoke_contract(
+        &test.vault_addr,
+        &Symbol::new(&test.env, "deposit"),
+        (1_000_000i128, &user, &user, &user).into_val(&test.env),
+    );
+
+    let (pt_minted, yt_minted) = ym.deposit(&user, &shares, &0, &0);
+    assert!(pt_minted > 0);
+    assert_eq!(pt_minted, yt_minted);
+
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+    assert_eq!(TokenClient::new(&test.env, &pt).balance(&user), pt_minted);
+    assert_eq!(TokenClient::new(&test.env, &yt).balance(&user), yt_minted);
+}
+
+#[test]
+fn test_deploy_liquidity_pools_sorts_tokens() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+
+    let (pt_pool, yt_pool) = client.deploy_liquidity_pools(&pt, &yt, &test.vault_addr);
+    assert_eq!(client.get_current_pt_pool(), Some(pt_pool.clone()));
+    assert_eq!(client.get_current_yt_pool(), Some(yt_pool.clone()));
+
+    // Both pools were constructed, which requires token_a < token_b
+    for pool in [pt_pool, yt_pool] {
+        let reserves: (i128, i128) = test.env.invoke_contract(
+            &pool,
+            &Symbol::new(&test.env, "get_rsrvs"),
+            ().into_val(&test.env),
+        );
+        assert_eq!(reserves, (0, 0));
+    }
+}
+
+#[test]
+fn test_consecutive_epochs_get_fresh_addresses() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    let first_ym = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let first_pt = client.get_current_pt_token().unwrap();
+    let first_yt = client.get_current_yt_token().unwrap();
+    let (first_pt_pool, first_yt_pool) =
+        client.deploy_liquidity_pools(&first_pt, &first_yt, &test.vault_addr);
+
+    // Same vault and maturity again: only the epoch differs
+    let second_ym = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let second_pt = client.get_current_pt_token().unwrap();
+    let second_yt = client.get_current_yt_token().unwrap();
+    let (second_pt_pool, second_yt_pool) =
+        client.deploy_liquidity_pools(&second_pt, &second_yt, &test.vault_addr);
+
+    assert_ne!(first_ym, second_ym);
+    assert_ne!(first_pt, second_pt);
+    assert_ne!(first_yt, second_yt);
+    assert_ne!(first_pt_pool, second_pt_pool);
+    assert_ne!(first_yt_pool, second_yt_pool);
+}
+
+#[test]
+fn test_token_names_carry_epoch() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let first_pt = TokenClient::new(&test.env, &client.get_current_pt_token().unwrap());
+    let first_yt = TokenClient::new(&test.env, &client.get_current_yt_token().unwrap());
+
+    client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let second_pt = TokenClient::new(&test.env, &client.get_current_pt_token().unwrap());
+    let second_yt = TokenClient::new(&test.env, &client.get_current_yt_token().unwrap());
+
+    let s = |value: &str| String::from_str(&test.env, value);
+    assert_eq!(first_pt.symbol(), s("PT-1"));
+    assert_eq!(first_pt.name(), s("Principal Token #1"));
+    assert_eq!(first_yt.symbol(), s("YT-1"));
+    assert_eq!(first_yt.name(), s("Yield Token #1"));
+    assert_eq!(second_pt.symbol(), s("PT-2"));
+    assert_eq!(second_yt.symbol(), s("YT-2"));
+    assert_ne!(first_pt.symbol(), second_pt.symbol());
+    assert_ne!(first_yt.symbol(), second_yt.symbol());
+}
+
+// Fee token with `balance` minted to a fresh payer, deploy fee set to 100
+fn setup_deploy_fee(test: &FactoryTest, balance: i128) -> (Address, Address) {
+    let fee_token = test
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&test.env))
+        .address();
+    let payer = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &fee_token).mint(&payer, &balance);
+    test.client().set_deploy_fee(&fee_token, &100);
+    (fee_token, payer)
+}
+
+#[test]
+fn test_permissionless_deploy_collects_fee() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let (fee_token, payer) = setup_deploy_fee(&test, 150);
+    assert_eq!(client.get_deploy_fee(), Some((fee_token.clone(), 100)));
+
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let ym = client.deploy_ym_permissionless(&test.vault_addr, &maturity, &payer);
+
+    // Only the payer signed, the admin wasn't involved
+    let signers: Vec<Address> = Vec::from_iter(&test.env, test.env.auths().into_iter().map(|(addr, _)| addr));
+    assert_eq!(signers, vec![&test.env, payer.clone()]);
+
+    let fee_client = TokenClient::new(&test.env, &fee_token);
+    assert_eq!(fee_client.balance(&payer), 50);
+    assert_eq!(fee_client.balance(&test.admin), 100);
+    assert!(YieldManagerClient::new(&test.env, &ym).is_initialized());
+
+    // Recorded only in the permissionless registry
+    assert_eq!(client.get_permissionless_deployments(), vec![&test.env, (ym, maturity)]);
+    assert_eq!(client.get_current_yield_manager(), None);
+    assert_eq!(client.get_all_yield_managers().len(), 0);
+}
+
+#[test]
+fn test_permissionless_deploy_leaves_current_epoch_alone() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let (_, payer) = setup_deploy_fee(&test, 100);
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let user = Address::generate(&test.env);
+
+    client.deploy_full_set(&test.vault_addr, &maturity);
+    let epoch = client.current_epoch();
+    let history = client.get_all_yield_managers();
+
+    StellarAssetClient::new(&test.env, &test.underlying_asset_addr).mint(&user, &1_000_000);
+    let shares: i128 = test.env.invoke_contract(
+        &test.vault_addr,
+        &Symbol::new(&test.env, "deposit"),
+        (1_000_000i128, &user, &user, &user).into_val(&test.env),
+    );
+    YieldManagerClient::new(&test.env, &epoch.0.clone().unwrap()).deposit(&user, &shares, &0, &0);
+
+    // Someone else deploys over a different vault
+    let other_vault = test.env.register(VAULT_WASM, (&test.underlying_asset_addr, 0u32));
+    client.deploy_ym_permissionless(&other_vault, &maturity, &payer);
+    assert_eq!(client.current_epoch(), epoch);
+    assert_eq!(client.get_all_yield_managers(), history);
+
+    // Rollover and migration still follow the admin's epochs
+    test.env.ledger().with_mut(|li| li.timestamp = maturity);
+    assert!(client.rollover_if_expired(&(maturity + 1000)));
+    let (pt_minted, _) = client.migrate_position(&user);
+    assert!(pt_minted > 0);
+}
+
+#[test]
+fn test_permissionless_deploys_keep_their_own_numbering() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let (_, payer) = setup_deploy_fee(&test, 200);
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let s = |value: &str| String::from_str(&test.env, value);
+    let pt_symbol = |ym: &Address| {
+        let (pt, _) = YieldManagerClient::new(&test.env, ym).get_token_contracts();
+        TokenClient::new(&test.env, &pt).symbol()
+    };
+
+    // Permissionless deploys of the same vault and maturity land between the admin's
+    let first = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let permissionless_first = client.deploy_ym_permissionless(&test.vault_addr, &maturity, &payer);
+    let second = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let permissionless_second = client.deploy_ym_permissionless(&test.vault_addr, &maturity, &payer);
+
+    // The admin's epochs run on uninterrupted, the permissionless deploys count apart
+    assert_eq!(pt_symbol(&first), s("PT-1"));
+    assert_eq!(pt_symbol(&second), s("PT-2"));
+    assert_eq!(pt_symbol(&permissionless_first), s("PT-P1"));
+    assert_eq!(pt_symbol(&permissionless_second), s("PT-P2"));
+    assert_ne!(first, permissionless_first);
+    assert_ne!(second, permissionless_second);
+}
+
+#[test]
+fn test_permissionless_deploy_rejected_when_fee_unpaid() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let (_, payer) = setup_deploy_fee(&test, 99);
+
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let result = client.try_deploy_ym_permissionless(&test.vault_addr, &maturity, &payer);
+    assert!(result.is_err());
+    assert_eq!(client.get_current_yield_manager(), None);
+}
+
+#[test]
+fn test_permissionless_deploy_requires_configured_fee() {
+    let test = FactoryTest::setup();
+    let payer = Address::generate(&test.env);
+
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let result = test
+        .client()
+        .try_deploy_ym_permissionless(&test.vault_addr, &maturity, &payer);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_rollover_deploys_new_epoch() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    let first_ym = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let first_pt = client.get_current_pt_token().unwrap();
+    let first_yt = client.get_current_yt_token().unwrap();
+    client.deploy_liquidity_pools(&first_pt, &first_yt, &test.vault_addr);
+
+    test.env.ledger().with_mut(|li| li.timestamp = maturity);
+    assert!(client.rollover_if_expired(&(maturity + 1000)));
+
+    let second_ym = client.get_current_yield_manager().unwrap();
+    assert_ne!(first_ym, second_ym);
+    assert_eq!(
+        YieldManagerClient::new(&test.env, &second_ym).get_maturity(),
+        maturity + 1000
+    );
+}
+
+#[test]
+fn test_migrate_position_after_rollover() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let user = Address::generate(&test.env);
+
+    let old_ym = YieldManagerClient::new(
+        &test.env,
+        &client.deploy_yield_manager(&test.vault_addr, &maturity),
+    );
+    let old_pt = TokenClient::new(&test.env, &client.get_current_pt_token().unwrap());
+
+    StellarAssetClient::new(&test.env, &test.underlying_asset_addr).mint(&user, &1_000_000);
+    let shares: i128 = test.env.invoke_contract(
+        &test.vault_addr,
+        &Symbol::new(&test.env, "deposit"),
+        (1_000_000i128, &user, &user, &user).into_val(&test.env),
+    );
+    let (old_pt_minted, _) = old_ym.deposit(&user, &shares, &0, &0);
+
+    test.env.ledger().with_mut(|li| li.timestamp = maturity);
+    assert!(client.rollover_if_expired(&(maturity + 1000)));
+
+    let (pt_minted, yt_minted) = client.migrate_position(&user);
+
+    // The old PT is gone and the shares now back the new epoch's PT and YT
+    let vault = TokenClient::new(&test.env, &test.vault_addr);
+    let new_ym = client.get_current_yield_manager().unwrap();
+    let new_pt = TokenClient::new(&test.env, &client.get_current_pt_token().unwrap());
+    let new_yt = TokenClient::new(&test.env, &client.get_current_yt_token().unwrap());
+    assert_eq!(old_pt.balance(&user), 0);
+    assert_eq!(pt_minted, old_pt_minted);
+    assert_eq!(new_pt.balance(&user), pt_minted);
+    assert_eq!(new_yt.balance(&user), yt_minted);
+    assert_eq!(vault.balance(&user), 0);
+    assert_eq!(vault.balance(&old_ym.address), 0);
+    assert_eq!(vault.balance(&new_ym), shares);
+}
+
+#[test]
+fn test_migrate_position_requires_matured_epoch() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let now = test.env.ledger().timestamp();
+    let user = Address::generate(&test.env);
+
+    // Two live epochs, the previous one hasn't matured
+    client.deploy_yield_manager(&test.vault_addr, &(now + 1000));
+    client.deploy_yield_manager(&test.vault_addr, &(now + 2000));
+
+    assert!(client.try_migrate_position(&user).is_err());
+}
+
+#[test]
+fn test_history_tracks_all_epochs() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let first_maturity = test.env.ledger().timestamp() + 1000;
+    let second_maturity = first_maturity + 1000;
+    let third_maturity = second_maturity + 1000;
+
+    assert_eq!(client.get_all_yield_managers().len(), 0);
+    assert_eq!(client.get_yield_manager_at(&0), None);
+
+    let first_ym = client.deploy_yield_manager(&test.vault_addr, &first_maturity);
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+    client.deploy_liquidity_pools(&pt, &yt, &test.vault_addr);
+
+    test.env.ledger().with_mut(|li| li.timestamp = first_maturity);
+    assert!(client.rollover_if_expired(&second_maturity));
+    let second_ym = client.get_current_yield_manager().unwrap();
+
+    test.env.ledger().with_mut(|li| li.timestamp = second_maturity);
+    assert!(client.rollover_if_expired(&third_maturity));
+    let third_ym = client.get_current_yield_manager().unwrap();
+
+    let history = client.get_all_yield_managers();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0), Some((first_ym.clone(), first_maturity)));
+    assert_eq!(history.get(1), Some((second_ym.clone(), second_maturity)));
+    assert_eq!(history.get(2), Some((third_ym.clone(), third_maturity)));
+
+    assert_eq!(client.get_yield_manager_at(&0), Some((first_ym.clone(), first_maturity)));
+    assert_eq!(client.get_yield_manager_at(&2), Some((third_ym, third_maturity)));
+    assert_eq!(client.get_yield_manager_at(&3), None);
+
+    // The matured manager is still reachable after rollover
+    assert_eq!(YieldManagerClient::new(&test.env, &first_ym).get_maturity(), first_maturity);
+}
+
+#[test]
+fn test_deploy_events() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    let ym = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let events = test.env.events().all();
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+    test.assert_factory_event(
+        &events,
+        &YieldManagerDeployed {
+            vault: test.vault_addr.clone(),
+            ym,
+            pt: pt.clone(),
+            yt: yt.clone(),
+            maturity,
+        },
+    );
+
+    let (pt_pool, yt_pool) = client.deploy_liquidity_pools(&pt, &yt, &test.vault_addr);
+    let events = test.env.events().all();
+    test.assert_factory_event(&events, &PoolsDeployed { pt_pool, yt_pool });
+}
+
+#[test]
+fn test_rollover_event() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+    let new_maturity = maturity + 1000;
+
+    let old_ym = client.deploy_yield_manager(&test.vault_addr, &maturity);
+    let pt = client.get_current_pt_token().unwrap();
+    let yt = client.get_current_yt_token().unwrap();
+    client.deploy_liquidity_pools(&pt, &yt, &test.vault_addr);
+
+    test.env.ledger().with_mut(|li| li.timestamp = maturity);
+    assert!(client.rollover_if_expired(&new_maturity));
+    let events = test.env.events().all();
+
+    let new_ym = client.get_current_yield_manager().unwrap();
+    let new_pt = client.get_current_pt_token().unwrap();
+    let new_yt = client.get_current_yt_token().unwrap();
+    test.assert_factory_event(
+        &events,
+        &Rollover {
+            old_ym,
+            new_ym: new_ym.clone(),
+            new_maturity,
+        },
+    );
+    test.assert_factory_event(
+        &events,
+        &YieldManagerDeployed {
+            vault: test.vault_addr.clone(),
+            ym: new_ym,
+            pt: new_pt,
+            yt: new_yt,
+            maturity: new_maturity,
+        },
+    );
+    test.assert_factory_event(
+        &events,
+        &PoolsDeployed {
+            pt_pool: client.get_current_pt_pool().unwrap(),
+            yt_pool: client.get_current_yt_pool().unwrap(),
+        },
+    );
+}
+
+#[test]
+fn test_current_epoch() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    assert_eq!(client.current_epoch(), (None, None, None, None, None));
+
+    let (ym_addr, pt, yt, pt_pool, yt_pool) = client.deploy_full_set(&test.vault_addr, &maturity);
+
+    assert_eq!(
+        client.current_epoch(),
+        (Some(ym_addr), Some(pt), Some(yt), Some(pt_pool), Some(yt_pool))
+    );
+}
+
+#[test]
+fn test_deploy_full_set() {
+    let test = FactoryTest::setup();
+    let client = test.client();
+    let maturity = test.env.ledger().timestamp() + 1000;
+
+    let (ym_addr, pt, yt, pt_pool, yt_pool) = client.deploy_full_set(&test.vault_addr, &maturity);
+
+    assert_eq!(client.get_current_yield_manager(), Some(ym_addr.clone()));
+    assert_eq!(client.get_current_pt_token(), Some(pt.clone()));
+    assert_eq!(client.get_current_yt_token(), Some(yt.clone()));
+    assert_eq!(client.get_current_pt_pool(), Some(pt_pool.clone()));
+    assert_eq!(client.get_current_yt_pool(), Some(yt_pool.clone()));
+
+    let ym = YieldManagerClient::new(&test.env, &ym_addr);
+    assert_eq!(ym.get_token_contracts(), (pt.clone(), yt.clone()));
+    assert_eq!(ym.get_maturity(), maturity);
+
+    // Fund a user with vault shares, PT and YT
+    let user = Address::generate(&test.env);
+    StellarAssetClient::new(&test.env, &test.underlying_asset_addr).mint(&user, &2_000_000);
+    let shares: i128 = test.env.invoke_contract(
+        &test.vault_addr,
+        &Symbol::new(&test.env, "deposit"),
+        (2_000_000i128, &user, &user, &user).into_val(&test.env),
+    );
+    ym.deposit(&user, &(shares / 2), &0, &0);
+
+    // Each pool pairs its token with the vault share and accepts liquidity
+    for (pool, token) in [(pt_pool, pt), (yt_pool, yt)] {
+        test.env.invoke_contract::<()>(
+            &pool,
+            &Symbol::new(&test.env, "deposit"),
+            (&user, 100_000i128, 100_000i128, 100_000i128, 100_000i128).into_val(&test.env),
+        );
+
+        let reserves: (i128, i128) = test.env.invoke_contract(
+            &pool,
+            &Symbol::new(&test.env, "get_rsrvs"),
+            ().into_val(&test.env),
+        );
+        assert_eq!(reserves, (100_000, 100_000));
+        assert_eq!(TokenClient::new(&test.env, &token).balance(&pool), 100_000);
+        assert_eq!(TokenClient::new(&test.env, &test.vault_addr).balance(&pool), 100_000);
+    }
+}
@@ -2,18 +2,44 @@
 
 mod storage;
 
-use soroban_sdk::{contract, contractimpl, token::TokenInterface, Address, Env, MuxedAddress, String};
+#[cfg(test)]
+mod test;
+
+use soroban_sdk::{
+    contract, contractimpl, token::TokenInterface, Address, Env, MuxedAddress, String, Vec,
+};
 use soroban_token_sdk::events::{Approve, Burn, Mint, Transfer};
 use storage::{
-    read_administrator, read_allowance, read_balance, read_decimal, read_name, read_symbol,
+    read_administrator, read_allowance, read_total_supply, read_balance, read_decimal, read_name, read_symbol,
     receive_balance, spend_allowance, spend_balance, write_administrator, write_allowance,
-    write_metadata, increase_total_supply, decrease_total_supply, TokenMetadata,
+    write_metadata, write_cap, read_cap, read_paused, write_paused, increase_total_supply, decrease_total_supply, TokenMetadata,
     INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD,
 };
 
 pub trait PrincipalTokenTrait {
-    fn __constructor(env: Env, admin: Address, name: String, symbol: String, decimals: u32);
+    fn __constructor(
+        env: Env,
+        admin: Address,
+        name: String,
+        symbol: String,
+        decimals: u32,
+        cap: i128,
+    );
+    fn cap(env: Env) -> i128;
+    fn total_supply(env: Env) -> i128;
+    fn pause(env: Env);
+    fn unpause(env: Env);
+    fn paused(env: Env) -> bool;
     fn mint(env: Env, to: Address, amount: i128);
+    fn mint_batch(env: Env, recipients: Vec<Address>, amounts: Vec<i128>);
+    fn burn_own(env: Env, from: Address, amount: i128);
+    fn set_metadata(env: Env, name: String, symbol: String);
+}
+
+fn check_nonnegative_amount(amount: i128) {
+    if amount < 0 {
+        panic!("negative amount is not allowed: {}", amount)
+    }
 }
 
 #[contract]
@@ -25,11 +51,12 @@ impl TokenInterface for PrincipalToken {
         env.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
-        read_allowance(&env, &from, &spender)
+        read_allowance(&env, &from, &spender).amount
     }
 
     fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
         from.require_auth();
+        check_nonnegative_amount(amount);
 
         env.storage()
             .instance()
@@ -55,6 +82,11 @@ impl TokenInterface for PrincipalToken {
 
     fn transfer(env: Env, from: Address, to: MuxedAddress, amount: i128) {
         from.require_auth();
+        check_nonnegative_amount(amount);
+
+        if read_paused(&env) {
+            panic!("paused");
+        }
 
         env.storage()
             .instance()
@@ -75,6 +107,11 @@ impl TokenInterface for PrincipalToken {
 
     fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
         spender.require_auth();
+        check_nonnegative_amount(amount);
+
+        if read_paused(&env) {
+            panic!("paused");
+        }
 
         env.storage()
             .instance()
@@ -96,6 +133,7 @@ impl TokenInterface for PrincipalToken {
     fn burn(env: Env, from: Address, amount: i128) {
         let admin = read_administrator(&env);
         admin.require_auth();
+        check_nonnegative_amount(amount);
 
         env.storage()
             .instance()
@@ -109,6 +147,7 @@ impl TokenInterface for PrincipalToken {
 
     fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
         spender.require_auth();
+        check_nonnegative_amount(amount);
 
         env.storage()
             .instance()
@@ -142,10 +181,14 @@ impl PrincipalTokenTrait for PrincipalToken {
         name: String,
         symbol: String,
         decimals: u32,
+        cap: i128,
     ) {
         if decimals > 18 {
             panic!("Decimal must not be greater than 18");
         }
+        if cap < 0 {
+            panic!("cap must not be negative");
+        }
 
         write_administrator(&env, &admin);
         write_metadata(
@@ -156,11 +199,39 @@ impl PrincipalTokenTrait for PrincipalToken {
                 decimals,
             },
         );
+        write_cap(&env, cap);
+    }
+
+    fn cap(env: Env) -> i128 {
+        read_cap(&env)
+    }
+
+    fn total_supply(env: Env) -> i128 {
+        read_total_supply(&env)
+    }
+
+    fn pause(env: Env) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_paused(&env, true);
+    }
+
+    fn unpause(env: Env) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        write_paused(&env, false);
+    }
+
+    fn paused(env: Env) -> bool {
+        read_paused(&env)
     }
 
     fn mint(env: Env, to: Address, amount: i128) {
         let admin = read_administrator(&env);
         admin.require_auth();
+        check_nonnegative_amount(amount);
 
         env.storage()
             .instance()
@@ -176,4 +247,67 @@ impl PrincipalTokenTrait for PrincipalToken {
         }
         .publish(&env);
     }
+
+    fn mint_batch(env: Env, recipients: Vec<Address>, amounts: Vec<i128>) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        if recipients.len() != amounts.len() {
+            panic!("recipients and amounts length mismatch");
+        }
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        let mut total_minted: i128 = 0;
+        for (to, amount) in recipients.iter().zip(amounts.iter()) {
+            check_nonnegative_amount(amount);
+            receive_balance(&env, &to, amount);
+            total_minted = total_minted
+                .checked_add(amount)
+                .expect("total minted overflow");
+
+            Mint {
+                to,
+                to_muxed_id: None,
+                amount,
+            }
+            .publish(&env);
+        }
+
+        // Supply is written once for the whole batch
+        increase_total_supply(&env, total_minted);
+    }
+
+    fn burn_own(env: Env, from: Address, amount: i128) {
+        // Holders can forgo principal without going through the yield manager
+        from.require_auth();
+        check_nonnegative_amount(amount);
+
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        spend_balance(&env, &from, amount);
+        decrease_total_supply(&env, amount);
+
+        Burn { from, amount }.publish(&env);
+    }
+
+    fn set_metadata(env: Env, name: String, symbol: String) {
+        let admin = read_administrator(&env);
+        admin.require_auth();
+
+        // Decimals are fixed at construction, balances are denominated in them
+        let decimals = read_decimal(&env);
+        write_metadata(
+            &env,
+            TokenMetadata {
+                name,
+                symbol,
+                decimals,
+            },
+        );
+    }
 }
@@ -16,6 +16,13 @@ pub struct TokenMetadata {
     pub decimals: u32,
 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
@@ -24,6 +31,8 @@ pub enum DataKey {
     Admin,
     Metadata,
     TotalSupply,
+    Cap,
+    Paused,
 }
 
 // Admin functions
@@ -95,9 +104,24 @@ pub fn spend_balance(e: &Env, addr: &Address, amount: i128) {
 }
 
 // Allowance functions
-pub fn read_allowance(e: &Env, from: &Address, spender: &Address) -> i128 {
+pub fn read_allowance(e: &Env, from: &Address, spender: &Address) -> AllowanceValue {
     let key = DataKey::Allowance(from.clone(), spender.clone());
-    e.storage().temporary().get(&key).unwrap_or(0)
+    if let Some(allowance) = e.storage().temporary().get::<DataKey, AllowanceValue>(&key) {
+        // The entry can outlive its expiration ledger, so check it explicitly
+        if allowance.expiration_ledger < e.ledger().sequence() {
+            AllowanceValue {
+                amount: 0,
+                expiration_ledger: allowance.expiration_ledger,
+            }
+        } else {
+            allowance
+        }
+    } else {
+        AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        }
+    }
 }
 
 pub fn write_allowance(
@@ -107,10 +131,19 @@ pub fn write_allowance(
     amount: i128,
     expiration_ledger: u32,
 ) {
+    if amount > 0 && expiration_ledger < e.ledger().sequence() {
+        panic!("expiration_ledger is less than ledger seq when amount > 0");
+    }
+
+    let allowance = AllowanceValue {
+        amount,
+        expiration_ledger,
+    };
+
     let key = DataKey::Allowance(from.clone(), spender.clone());
-    e.storage().temporary().set(&key, &amount);
+    e.storage().temporary().set(&key, &allowance);
 
-    if expiration_ledger > 0 {
+    if amount > 0 {
         let ledger = e.ledger().sequence();
         let live_for = expiration_ledger.saturating_sub(ledger);
         e.storage().temporary().extend_ttl(&key, live_for, live_for);
@@ -119,10 +152,18 @@ pub fn write_allowance(
 
 pub fn spend_allowance(e: &Env, from: &Address, spender: &Address, amount: i128) {
     let allowance = read_allowance(e, from, spender);
-    if allowance < amount {
+    if allowance.amount < amount {
         panic!("insufficient allowance");
     }
-    write_allowance(e, from, spender, allowance - amount, 0);
+    if amount > 0 {
+        write_allowance(
+            e,
+            from,
+            spender,
+            allowance.amount - amount,
+            allowance.expiration_ledger,
+        );
+    }
 }
 
 // Total supply functions
@@ -137,11 +178,37 @@ pub fn write_total_supply(e: &Env, amount: i128) {
 }
 
 pub fn increase_total_supply(e: &Env, amount: i128) {
-    let total_supply = read_total_supply(e);
-    write_total_supply(e, total_supply + amount);
+    let total_supply = read_total_supply(e) + amount;
+    let cap = read_cap(e);
+    if cap > 0 && total_supply > cap {
+        panic!("cap exceeded");
+    }
+    write_total_supply(e, total_supply);
 }
 
 pub fn decrease_total_supply(e: &Env, amount: i128) {
     let total_supply = read_total_supply(e);
     write_total_supply(e, total_supply - amount);
 }
+
+// Supply cap functions (0 means uncapped)
+pub fn read_cap(e: &Env) -> i128 {
+    let key = DataKey::Cap;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+pub fn write_cap(e: &Env, cap: i128) {
+    let key = DataKey::Cap;
+    e.storage().instance().set(&key, &cap);
+}
+
+// Pause functions (freezes holder transfers only)
+pub fn read_paused(e: &Env) -> bool {
+    let key = DataKey::Paused;
+    e.storage().instance().get(&key).unwrap_or(false)
+}
+
+pub fn write_paused(e: &Env, paused: bool) {
+    let key = DataKey::Paused;
+    e.storage().instance().set(&key, &paused);
+}
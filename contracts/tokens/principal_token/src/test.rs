@@ -0,0 +1,311 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::{PrincipalToken, PrincipalTokenClient};
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Ledger, MockAuth, MockAuthInvoke},
+    vec, Address, Env, IntoVal, String, Symbol,
+};
+
+struct PrincipalTokenTest<'a> {
+    env: Env,
+    user1: Address,
+    user2: Address,
+    token: PrincipalTokenClient<'a>,
+}
+
+impl<'a> PrincipalTokenTest<'a> {
+    fn setup() -> Self {
+        Self::setup_with_cap(0)
+    }
+
+    fn setup_with_cap(cap: i128) -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+
+        let token_id = env.register(
+            PrincipalToken,
+            (
+                &admin,
+                String::from_str(&env, "Principal Token"),
+                String::from_str(&env, "PT"),
+                7u32,
+                cap,
+            ),
+        );
+        let token = PrincipalTokenClient::new(&env, &token_id);
+
+        PrincipalTokenTest {
+            env,
+            user1,
+            user2,
+            token,
+        }
+    }
+
+    fn advance_ledgers(&self, ledgers: u32) {
+        self.env.ledger().with_mut(|li| {
+            li.sequence_number += ledgers;
+        });
+    }
+}
+
+#[test]
+fn test_allowance_reads_zero_after_expiration() {
+    let test = PrincipalTokenTest::setup();
+
+    let expiration_ledger = test.env.ledger().sequence() + 1;
+    test.token.approve(&test.user1, &test.user2, &1_000, &expiration_ledger);
+    assert_eq!(test.token.allowance(&test.user1, &test.user2), 1_000);
+
+    // Still valid on the expiration ledger itself
+    test.advance_ledgers(1);
+    assert_eq!(test.token.allowance(&test.user1, &test.user2), 1_000);
+
+    // The temporary entry is still live here, but the allowance has expired
+    test.advance_ledgers(1);
+    assert_eq!(test.token.allowance(&test.user1, &test.user2), 0);
+}
+
+#[test]
+fn test_mint_batch_mints_to_each_recipient() {
+    let test = PrincipalTokenTest::setup();
+    let user3 = Address::generate(&test.env);
+
+    test.token.mint_batch(
+        &vec![&test.env, test.user1.clone(), test.user2.clone(), user3.clone()],
+        &vec![&test.env, 100i128, 250i128, 650i128],
+    );
+
+    assert_eq!(test.token.balance(&test.user1), 100);
+    assert_eq!(test.token.balance(&test.user2), 250);
+    assert_eq!(test.token.balance(&user3), 650);
+    assert_eq!(test.token.total_supply(), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "recipients and amounts length mismatch")]
+fn test_mint_batch_length_mismatch() {
+    let test = PrincipalTokenTest::setup();
+
+    test.token.mint_batch(
+        &vec![&test.env, test.user1.clone(), test.user2.clone()],
+        &vec![&test.env, 100i128],
+    );
+}
+
+#[test]
+fn test_mint_up_to_cap() {
+    let test = PrincipalTokenTest::setup_with_cap(1_000);
+    assert_eq!(test.token.cap(), 1_000);
+
+    test.token.mint(&test.user1, &600);
+    test.token.mint(&test.user2, &400);
+    assert_eq!(test.token.total_supply(), 1_000);
+
+    // Burning frees room under the cap again
+    test.token.burn(&test.user1, &100);
+    test.token.mint(&test.user2, &100);
+    assert_eq!(test.token.total_supply(), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "cap exceeded")]
+fn test_mint_over_cap() {
+    let test = PrincipalTokenTest::setup_with_cap(1_000);
+
+    test.token.mint(&test.user1, &1_000);
+    test.token.mint(&test.user1, &1);
+}
+
+#[test]
+#[should_panic(expected = "cap exceeded")]
+fn test_mint_batch_over_cap() {
+    let test = PrincipalTokenTest::setup_with_cap(1_000);
+
+    test.token.mint_batch(
+        &vec![&test.env, test.user1.clone(), test.user2.clone()],
+        &vec![&test.env, 600i128, 401i128],
+    );
+}
+
+#[test]
+fn test_zero_cap_is_uncapped() {
+    let test = PrincipalTokenTest::setup();
+    assert_eq!(test.token.cap(), 0);
+
+    test.token.mint(&test.user1, &i64::MAX.into());
+    assert_eq!(test.token.total_supply(), i64::MAX as i128);
+}
+
+#[test]
+fn test_pause_blocks_transfers_but_not_burn() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+
+    test.token.pause();
+    assert!(test.token.paused());
+
+    assert!(test.token.try_transfer(&test.user1, &test.user2, &100).is_err());
+
+    let expiration_ledger = test.env.ledger().sequence() + 100;
+    test.token.approve(&test.user1, &test.user2, &100, &expiration_ledger);
+    assert!(test
+        .token
+        .try_transfer_from(&test.user2, &test.user1, &test.user2, &100)
+        .is_err());
+
+    // The yield manager can still mint and burn on redemption
+    test.token.burn(&test.user1, &400);
+    test.token.mint(&test.user1, &100);
+    assert_eq!(test.token.balance(&test.user1), 700);
+
+    test.token.unpause();
+    assert!(!test.token.paused());
+    test.token.transfer(&test.user1, &test.user2, &100);
+    assert_eq!(test.token.balance(&test.user2), 100);
+}
+
+#[test]
+#[should_panic(expected = "paused")]
+fn test_transfer_while_paused() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+
+    test.token.pause();
+    test.token.transfer(&test.user1, &test.user2, &100);
+}
+
+#[test]
+fn test_total_supply_tracks_mint_and_burn() {
+    let test = PrincipalTokenTest::setup();
+    assert_eq!(test.token.total_supply(), 0);
+
+    test.token.mint(&test.user1, &1_000);
+    test.token.mint(&test.user2, &500);
+    assert_eq!(test.token.total_supply(), 1_500);
+
+    test.token.burn(&test.user1, &300);
+    assert_eq!(test.token.total_supply(), 1_200);
+
+    // Transfers move balances without changing supply
+    test.token.transfer(&test.user2, &test.user1, &200);
+    assert_eq!(test.token.total_supply(), 1_200);
+}
+
+#[test]
+#[should_panic(expected = "insufficient allowance")]
+fn test_transfer_from_after_expiration() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+
+    let expiration_ledger = test.env.ledger().sequence() + 1;
+    test.token.approve(&test.user1, &test.user2, &500, &expiration_ledger);
+
+    // Past the expiration the stored amount can no longer be spent
+    test.advance_ledgers(2);
+    test.token.transfer_from(&test.user2, &test.user1, &test.user2, &100);
+}
+
+#[test]
+#[should_panic(expected = "expiration_ledger is less than ledger seq when amount > 0")]
+fn test_approve_with_past_expiration() {
+    let test = PrincipalTokenTest::setup();
+
+    test.advance_ledgers(10);
+    let expiration_ledger = test.env.ledger().sequence() - 1;
+    test.token.approve(&test.user1, &test.user2, &500, &expiration_ledger);
+}
+
+#[test]
+fn test_burn_own_reduces_supply() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+    test.token.mint(&test.user2, &500);
+
+    test.token.burn_own(&test.user1, &400);
+
+    // Only the holder signed, not the admin
+    assert_eq!(
+        test.env.auths(),
+        std::vec![(
+            test.user1.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    test.token.address.clone(),
+                    Symbol::new(&test.env, "burn_own"),
+                    (&test.user1, 400i128).into_val(&test.env),
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    assert_eq!(test.token.balance(&test.user1), 600);
+    assert_eq!(test.token.total_supply(), 1_100);
+}
+
+#[test]
+#[should_panic(expected = "insufficient balance")]
+fn test_burn_own_more_than_balance() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+
+    test.token.burn_own(&test.user1, &1_001);
+}
+
+#[test]
+#[should_panic(expected = "negative amount is not allowed")]
+fn test_burn_own_negative_amount() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user1, &1_000);
+
+    // A negative burn would otherwise credit the holder and grow the supply past the cap
+    test.token.burn_own(&test.user1, &-5_000);
+}
+
+#[test]
+#[should_panic(expected = "negative amount is not allowed")]
+fn test_transfer_negative_amount() {
+    let test = PrincipalTokenTest::setup();
+    test.token.mint(&test.user2, &1_000);
+
+    // A negative transfer would otherwise pull from the recipient
+    test.token.transfer(&test.user1, &test.user2, &-500);
+}
+
+#[test]
+fn test_set_metadata_updates_name_and_symbol() {
+    let test = PrincipalTokenTest::setup();
+
+    let name = String::from_str(&test.env, "Principal Token 2026-12");
+    let symbol = String::from_str(&test.env, "PT-1");
+    test.token.set_metadata(&name, &symbol);
+
+    assert_eq!(test.token.name(), name);
+    assert_eq!(test.token.symbol(), symbol);
+    assert_eq!(test.token.decimals(), 7);
+}
+
+#[test]
+fn test_set_metadata_requires_admin() {
+    let test = PrincipalTokenTest::setup();
+
+    let name = String::from_str(&test.env, "Fake Token");
+    let symbol = String::from_str(&test.env, "FAKE");
+    test.env.mock_auths(&[MockAuth {
+        address: &test.user1,
+        invoke: &MockAuthInvoke {
+            contract: &test.token.address,
+            fn_name: "set_metadata",
+            args: (&name, &symbol).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+
+    assert!(test.token.try_set_metadata(&name, &symbol).is_err());
+    assert_eq!(test.token.symbol(), String::from_str(&test.env, "PT"));
+}
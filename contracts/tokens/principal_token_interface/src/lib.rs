@@ -4,8 +4,30 @@ use soroban_sdk::token::TokenInterface;
 
 #[contractclient(name = "PrincipalTokenClient")]
 pub trait PrincipalTokenTrait: TokenInterface {
-    fn __constructor(env: Env, admin: Address, name: String, symbol: String, decimals: u32);
+    fn __constructor(
+        env: Env,
+        admin: Address,
+        name: String,
+        symbol: String,
+        decimals: u32,
+        cap: i128,
+    );
+
+    // Maximum PT supply, 0 when uncapped
+    fn cap(env: Env) -> i128;
+    fn total_supply(env: Env) -> i128;
+
+    // Transfers are frozen while paused, mint and burn keep working
+    fn pause(env: Env);
+    fn unpause(env: Env);
+    fn paused(env: Env) -> bool;
 
     // Custom mint function for yield manager control
     fn mint(env: Env, to: Address, amount: i128);
+
+    // Burns the caller's own PT, no admin auth involved
+    fn burn_own(env: Env, from: Address, amount: i128);
+
+    // Renames the token, decimals stay fixed
+    fn set_metadata(env: Env, name: String, symbol: String);
 }
@@ -1,17 +1,32 @@
 use soroban_sdk::{
-    contract, contractimpl, token::TokenInterface, Address, Env, MuxedAddress, String,
+    contract, contractimpl, token, token::TokenInterface, Address, Env, MuxedAddress, String, I256,
 };
-use yield_manager_interface::YieldManagerClient;
+use soroban_token_sdk::events::{Approve, Burn, Mint, Transfer};
+use vault_interface::VaultContractClient;
+use yield_manager_interface::{VaultType, YieldManagerClient};
+use crate::events::Claim;
 use crate::storage;
 
 pub trait YieldTokenCustomTrait {
     fn __constructor(env: Env, admin: Address, decimal: u32, name: String, symbol: String);
     fn mint(env: Env, to: Address, amount: i128, exchange_rate: i128);
+    fn burn_with_rate(env: Env, from: Address, amount: i128, exchange_rate: i128);
+    fn total_supply(env: Env) -> i128;
     fn user_index(env: Env, address: Address) -> i128;
     fn accrued_yield(env: Env, address: Address) -> i128;
+    fn total_unclaimed_yield(env: Env) -> i128;
+    fn total_unclaimed_yield_with_rate(env: Env, exchange_rate: i128) -> i128;
+    fn claimable_yield(env: Env, user: Address) -> i128;
+    fn dry_run_claim(env: Env, user: Address) -> i128;
+    fn checkpoint(env: Env, user: Address) -> i128;
     fn claim_yield(env: Env, user: Address) -> i128;
+    fn claim_yield_as_assets(env: Env, user: Address) -> i128;
+    fn take_accrued_yield(env: Env, user: Address, max_amount: i128, exchange_rate: i128) -> i128;
 }
 
+// Fixed point precision of the share claims aggregate, see storage::get_total_share_claims
+const SHARE_CLAIM_PRECISION: i128 = 1_000_000_000_000_000_000;
+
 fn check_nonnegative_amount(amount: i128) {
     if amount < 0 {
         panic!("negative amount is not allowed: {}", amount)
@@ -27,26 +42,63 @@ impl YieldToken {
         YieldManagerClient::new(env, &yield_manager).get_exchange_rate()
     }
 
-    fn accrue_yield(env: &Env, user: &Address, rate_hint: Option<i128>) -> i128 {
+    // Yield in vault shares earned by `balance` since the rate moved from `old_index`.
+    // balance was minted as shares * old_index, and PT redeems those shares' principal at
+    // shares * old_index / current_rate, so the YT side owes balance / old_index - balance /
+    // current_rate shares: balance * (current_rate - old_index) / old_index / current_rate
+    fn pending_yield(env: &Env, balance: i128, old_index: i128, current_rate: i128) -> i128 {
+        // balance is shares * rate, so balance * rate delta can exceed i128: widen for the product
+        let pending = I256::from_i128(env, balance)
+            .mul(&I256::from_i128(env, current_rate - old_index))
+            .div(&I256::from_i128(env, old_index))
+            .div(&I256::from_i128(env, current_rate));
+        pending.to_i128().expect("pending yield overflow")
+    }
+
+    // Vault shares `balance` stood for at `index`, scaled by SHARE_CLAIM_PRECISION
+    fn share_claim(env: &Env, balance: i128, index: i128) -> I256 {
+        if balance == 0 || index == 0 {
+            return I256::from_i32(env, 0);
+        }
+        I256::from_i128(env, balance)
+            .mul(&I256::from_i128(env, SHARE_CLAIM_PRECISION))
+            .div(&I256::from_i128(env, index))
+    }
+
+    fn adjust_total_share_claims(env: &Env, old_claim: I256, new_claim: I256) {
+        let total = storage::get_total_share_claims(env);
+        storage::set_total_share_claims(env, &total.sub(&old_claim).add(&new_claim));
+    }
+
+    // Writes `user`'s balance at their current index, keeping the share claims total in step
+    fn write_balance(env: &Env, user: &Address, old_balance: i128, new_balance: i128) {
+        let index = storage::get_user_index(env, user);
+        Self::adjust_total_share_claims(
+            env,
+            Self::share_claim(env, old_balance, index),
+            Self::share_claim(env, new_balance, index),
+        );
+        storage::set_balance(env, user, new_balance);
+    }
+
+    // Accrues `user`'s pending yield up to `current_rate`. Storage only: callers resolve the
+    // rate first (one manager call, or the rate the manager passed in) so no external call
+    // sits between these reads and writes
+    fn accrue_yield(env: &Env, user: &Address, current_rate: i128) {
         let balance = storage::get_balance(env, user);
         let old_index = storage::get_user_index(env, user);
 
-        //YM contract mints, but it cant re-enter. Rate is provided by the YM contract
-        let current_rate: i128 = if let Some(rate) = rate_hint {
-            rate
-        } else {
-            Self::get_exchange_rate(env)
-        };
-
         // Initialize index for new users (even if they have no balance yet)
         if old_index == 0 {
             storage::set_user_index(env, user, current_rate);
-            return current_rate;
+            return;
         }
 
-        // Early return if no balance (but index is already initialized above)
+        // No balance earns nothing, but the index still moves to the current rate so
+        // tokens received later don't earn from the rate the balance last went to zero at
         if balance == 0 {
-            return current_rate;
+            storage::set_user_index(env, user, current_rate);
+            return;
         }
 
         // The yield manager guarantees the exchange rate never decreases
@@ -54,35 +106,145 @@ impl YieldToken {
         // This contract only update if rate increased to avoid unnecessary storage writes
         if current_rate > old_index {
             // Calculate pending yield in vault shares
-            // balance and rates are scaled by 1e7
-            let pending_yield = (balance * (current_rate - old_index)) / old_index / 10_000_000;
+            let pending_yield = Self::pending_yield(env, balance, old_index, current_rate);
             let current_accrued = storage::get_accrued_yield(env, user);
-            storage::set_accrued_yield(env, user, current_accrued + pending_yield);
+            let accrued = current_accrued
+                .checked_add(pending_yield)
+                .expect("accrued yield overflow");
+            storage::set_accrued_yield(env, user, accrued);
+            Self::adjust_total_accrued_yield(env, pending_yield);
+            Self::adjust_total_share_claims(
+                env,
+                Self::share_claim(env, balance, old_index),
+                Self::share_claim(env, balance, current_rate),
+            );
             storage::set_user_index(env, user, current_rate);
         }
 
         // If the rate hasn't gone up no yield to accrue, no storage update needed
-        current_rate
+    }
+
+    // Accrued plus pending yield over all holders at `current_rate`, see total_unclaimed_yield
+    fn unclaimed_yield_at(env: &Env, current_rate: i128) -> i128 {
+        let accrued = storage::get_total_accrued_yield(env);
+        let total_supply = storage::get_total_supply(env);
+        if total_supply == 0 {
+            return accrued;
+        }
+
+        let pending = storage::get_total_share_claims(env)
+            .sub(&Self::share_claim(env, total_supply, current_rate))
+            .div(&I256::from_i128(env, SHARE_CLAIM_PRECISION))
+            .to_i128()
+            .expect("pending yield overflow");
+
+        accrued + pending.max(0)
+    }
+
+    fn adjust_total_accrued_yield(env: &Env, delta: i128) {
+        let total = storage::get_total_accrued_yield(env)
+            .checked_add(delta)
+            .expect("total accrued yield overflow");
+        storage::set_total_accrued_yield(env, total);
+    }
+
+    // Accrues and claims `user`'s yield, with the manager paying the vault shares to `to`
+    fn claim_to(env: &Env, user: &Address, to: &Address) -> i128 {
+        let current_rate = Self::get_exchange_rate(env);
+        Self::accrue_yield(env, user, current_rate);
+
+        let claimable = storage::get_accrued_yield(env, user);
+        if claimable == 0 {
+            return 0;
+        }
+
+        storage::set_accrued_yield(env, user, 0);
+
+        // Call yield manager (admin) to distribute vault shares
+        let yield_manager = storage::get_admin(env);
+        let yield_manager_client = YieldManagerClient::new(env, &yield_manager);
+        let distributed = yield_manager_client.distribute_yield(to, &claimable);
+
+        // The manager caps payouts at its balance, anything it couldn't pay stays claimable
+        if distributed < claimable {
+            storage::set_accrued_yield(env, user, claimable - distributed);
+        }
+        Self::adjust_total_accrued_yield(env, -distributed);
+
+        Claim {
+            user: user.clone(),
+            amount: distributed,
+        }
+        .publish(env);
+
+        distributed
+    }
+
+    // Both sides accrue at their old balances before any tokens move
+    fn move_balance(env: &Env, from: &Address, to: &Address, amount: i128) {
+        // Single manager call, made before any balance is read so everything after it
+        // (read, accrue, write) is local
+        let current_rate = Self::get_exchange_rate(env);
+
+        let from_balance = storage::get_balance(env, from);
+        if from_balance < amount {
+            panic!("Insufficient balance");
+        }
+        let to_balance = storage::get_balance(env, to);
+
+        Self::accrue_yield(env, from, current_rate);
+        Self::accrue_yield(env, to, current_rate);
+
+        if from == to {
+            return;
+        }
+        Self::write_balance(env, from, from_balance, from_balance - amount);
+        Self::write_balance(env, to, to_balance, to_balance + amount);
+    }
+
+    fn burn_balance(env: &Env, from: &Address, amount: i128) {
+        // As in move_balance, resolve the rate before reading what gets written back
+        let current_rate = Self::get_exchange_rate(env);
+
+        let balance = storage::get_balance(env, from);
+        if balance < amount {
+            panic!("Insufficient balance");
+        }
+        let total_supply = storage::get_total_supply(env);
+
+        Self::accrue_yield(env, from, current_rate);
+
+        Self::write_balance(env, from, balance, balance - amount);
+        storage::set_total_supply(env, total_supply - amount);
     }
 }
 
 // SEP-41 TokenInterface implementation
 #[contractimpl]
 impl TokenInterface for YieldToken {
-    fn allowance(_env: Env, _from: Address, _spender: Address) -> i128 {
-        // Placeholder: YieldToken doesn't support allowances
-        0
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        storage::get_allowance(&env, &from, &spender).amount
     }
 
     fn approve(
-        _env: Env,
-        _from: Address,
-        _spender: Address,
-        _amount: i128,
-        _expiration_ledger: u32,
+        env: Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
     ) {
-        // Placeholder: YieldToken doesn't support approvals
-        panic!("approve not supported for YieldToken");
+        from.require_auth();
+        check_nonnegative_amount(amount);
+
+        storage::set_allowance(&env, &from, &spender, amount, expiration_ledger);
+
+        Approve {
+            from,
+            spender,
+            amount,
+            expiration_ledger,
+        }
+        .publish(&env);
     }
 
     fn balance(env: Env, id: Address) -> i128 {
@@ -95,51 +257,56 @@ impl TokenInterface for YieldToken {
 
         let to: Address = to_muxed.address();
 
-        let from_balance = storage::get_balance(&env, &from);
-        if from_balance < amount {
-            panic!("Insufficient balance");
-        }
-
-        Self::accrue_yield(&env, &from, None);
-        Self::accrue_yield(&env, &to, None);
+        Self::move_balance(&env, &from, &to, amount);
 
-        let to_balance = storage::get_balance(&env, &to);
-
-        storage::set_balance(&env, &from, from_balance - amount);
-        storage::set_balance(&env, &to, to_balance + amount);
+        Transfer {
+            from,
+            to,
+            to_muxed_id: to_muxed.id(),
+            amount,
+        }
+        .publish(&env);
     }
 
     fn transfer_from(
-        _env: Env,
-        _spender: Address,
-        _from: Address,
-        _to: Address,
-        _amount: i128,
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        amount: i128,
     ) {
-        // Placeholder: YieldToken doesn't support allowance-based transfers
-        panic!("transfer_from not supported for YieldToken");
+        spender.require_auth();
+        check_nonnegative_amount(amount);
+
+        storage::spend_allowance(&env, &from, &spender, amount);
+        Self::move_balance(&env, &from, &to, amount);
+
+        Transfer {
+            from,
+            to,
+            to_muxed_id: None,
+            amount,
+        }
+        .publish(&env);
     }
 
     fn burn(env: Env, from: Address, amount: i128) {
         from.require_auth();
         check_nonnegative_amount(amount);
 
-        let balance = storage::get_balance(&env, &from);
-        if balance < amount {
-            panic!("Insufficient balance");
-        }
+        Self::burn_balance(&env, &from, amount);
 
-        Self::accrue_yield(&env, &from, None);
+        Burn { from, amount }.publish(&env);
+    }
 
-        storage::set_balance(&env, &from, balance - amount);
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        check_nonnegative_amount(amount);
 
-        let total_supply = storage::get_total_supply(&env);
-        storage::set_total_supply(&env, total_supply - amount);
-    }
+        storage::spend_allowance(&env, &from, &spender, amount);
+        Self::burn_balance(&env, &from, amount);
 
-    fn burn_from(_env: Env, _spender: Address, _from: Address, _amount: i128) {
-        // Placeholder: YieldToken doesn't support allowance-based burns
-        panic!("burn_from not supported for YieldToken");
+        Burn { from, amount }.publish(&env);
     }
 
     fn decimals(env: Env) -> u32 {
@@ -177,13 +344,46 @@ impl YieldTokenCustomTrait for YieldToken {
         admin.require_auth();
         check_nonnegative_amount(amount);
 
-        Self::accrue_yield(&env, &to, Some(exchange_rate));
+        // YM mints, it cant re-enter so it provides the rate
+        Self::accrue_yield(&env, &to, exchange_rate);
 
         let balance = storage::get_balance(&env, &to);
-        storage::set_balance(&env, &to, balance + amount);
+        Self::write_balance(&env, &to, balance, balance + amount);
 
         let total_supply = storage::get_total_supply(&env);
         storage::set_total_supply(&env, total_supply + amount);
+
+        Mint {
+            to,
+            to_muxed_id: None,
+            amount,
+        }
+        .publish(&env);
+    }
+
+    fn burn_with_rate(env: Env, from: Address, amount: i128, exchange_rate: i128) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+        check_nonnegative_amount(amount);
+
+        let balance = storage::get_balance(&env, &from);
+        if balance < amount {
+            panic!("Insufficient balance");
+        }
+
+        // YM burns when recombining PT and YT, it cant re-enter so it provides the rate
+        Self::accrue_yield(&env, &from, exchange_rate);
+
+        Self::write_balance(&env, &from, balance, balance - amount);
+
+        let total_supply = storage::get_total_supply(&env);
+        storage::set_total_supply(&env, total_supply - amount);
+
+        Burn { from, amount }.publish(&env);
+    }
+
+    fn total_supply(env: Env) -> i128 {
+        storage::get_total_supply(&env)
     }
 
     fn user_index(env: Env, address: Address) -> i128 {
@@ -194,23 +394,111 @@ impl YieldTokenCustomTrait for YieldToken {
         storage::get_accrued_yield(&env, &address)
     }
 
+    fn total_unclaimed_yield(env: Env) -> i128 {
+        // Sum of `claimable_yield` over all holders. Each holder's pending yield is
+        // balance / index - balance / current_rate shares, so summed over holders it is the
+        // share claims total less total_supply / current_rate, with no holder visited.
+        // Matches the per-holder sum up to each holder's pending yield rounding down.
+        // With no supply nothing is pending, so the manager isn't asked for the rate
+        if storage::get_total_supply(&env) == 0 {
+            return storage::get_total_accrued_yield(&env);
+        }
+
+        // Read-only, like claimable_yield
+        let yield_manager = storage::get_admin(&env);
+        let current_rate = YieldManagerClient::new(&env, &yield_manager).peek_exchange_rate();
+        Self::unclaimed_yield_at(&env, current_rate)
+    }
+
+    fn total_unclaimed_yield_with_rate(env: Env, exchange_rate: i128) -> i128 {
+        // The YM can't be called back while it asks, so it provides the rate
+        Self::unclaimed_yield_at(&env, exchange_rate)
+    }
+
+    fn claimable_yield(env: Env, user: Address) -> i128 {
+        let accrued = storage::get_accrued_yield(&env, &user);
+        let balance = storage::get_balance(&env, &user);
+        let old_index = storage::get_user_index(&env, &user);
+        if balance == 0 || old_index == 0 {
+            return accrued;
+        }
+
+        // Read-only: peek the rate so nothing is written on either contract
+        let yield_manager = storage::get_admin(&env);
+        let current_rate = YieldManagerClient::new(&env, &yield_manager).peek_exchange_rate();
+        if current_rate <= old_index {
+            return accrued;
+        }
+
+        accrued + Self::pending_yield(&env, balance, old_index, current_rate)
+    }
+
+    fn dry_run_claim(env: Env, user: Address) -> i128 {
+        // What claim_yield would distribute right now, without writes or transfers
+        Self::claimable_yield(env, user)
+    }
+
+    fn checkpoint(env: Env, user: Address) -> i128 {
+        // Moves pending yield into accrued_yield without paying it out. Nothing leaves the
+        // user, so no auth: it's the same accrual a transfer or mint would trigger
+        let current_rate = Self::get_exchange_rate(&env);
+        Self::accrue_yield(&env, &user, current_rate);
+
+        storage::get_accrued_yield(&env, &user)
+    }
+
     fn claim_yield(env: Env, user: Address) -> i128 {
         user.require_auth();
 
-        Self::accrue_yield(&env, &user, None);
+        Self::claim_to(&env, &user, &user)
+    }
 
-        let claimable = storage::get_accrued_yield(&env, &user);
-        if claimable == 0 {
+    fn claim_yield_as_assets(env: Env, user: Address) -> i128 {
+        user.require_auth();
+
+        let yield_manager = storage::get_admin(&env);
+        let yield_manager_client = YieldManagerClient::new(&env, &yield_manager);
+        if yield_manager_client.get_vault_type() != VaultType::Vault4626 {
+            panic!("vault type not supported");
+        }
+        let vault = yield_manager_client.get_vault();
+
+        // The shares are paid to this contract first, net of any protocol fee
+        let this = env.current_contract_address();
+        let vault_token_client = token::Client::new(&env, &vault);
+        let shares_before = vault_token_client.balance(&this);
+        Self::claim_to(&env, &user, &this);
+        let shares = vault_token_client.balance(&this) - shares_before;
+        if shares == 0 {
             return 0;
         }
 
-        storage::set_accrued_yield(&env, &user, 0);
+        VaultContractClient::new(&env, &vault).redeem(&shares, &user, &this, &this)
+    }
 
-        // Call yield manager (admin) to distribute vault shares
-        let yield_manager = storage::get_admin(&env);
-        let yield_manager_client = YieldManagerClient::new(&env, &yield_manager);
-        yield_manager_client.distribute_yield(&user, &claimable);
+    fn take_accrued_yield(env: Env, user: Address, max_amount: i128, exchange_rate: i128) -> i128 {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
 
-        claimable
+        // YM compounds the yield it already holds, it cant re-enter so it provides the rate
+        Self::accrue_yield(&env, &user, exchange_rate);
+
+        let accrued = storage::get_accrued_yield(&env, &user);
+        let taken = accrued.min(max_amount);
+        if taken <= 0 {
+            return 0;
+        }
+
+        // Anything over the cap stays claimable, like a partially paid claim
+        storage::set_accrued_yield(&env, &user, accrued - taken);
+        Self::adjust_total_accrued_yield(&env, -taken);
+
+        Claim {
+            user,
+            amount: taken,
+        }
+        .publish(&env);
+
+        taken
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,9 @@
+use soroban_sdk::{contractevent, Address};
+
+// Published when a holder claims accrued yield, amount is in vault shares
+#[contractevent(topics = ["claim"], data_format = "single-value")]
+pub struct Claim {
+    #[topic]
+    pub user: Address,
+    pub amount: i128,
+}
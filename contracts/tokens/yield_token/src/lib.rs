@@ -3,6 +3,8 @@
 #[cfg(feature = "contract")]
 mod contract;
 #[cfg(feature = "contract")]
+mod events;
+#[cfg(feature = "contract")]
 mod storage;
 
 #[cfg(all(test, feature = "contract"))]
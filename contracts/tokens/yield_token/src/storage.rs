@@ -1,4 +1,9 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use soroban_sdk::{contracttype, Address, Env, String, I256};
+
+// Storage TTL constants
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
 
 #[contracttype]
 #[derive(Clone)]
@@ -8,9 +13,17 @@ pub struct TokenMetadata {
     pub decimal: u32,
 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
+    Allowance(Address, Address),
     Balance(Address),
     UserIndex(Address), // vault exchange rate the user last interacted at
     AccruedYield(Address),
@@ -20,6 +33,8 @@ pub enum DataKey {
 const ADMIN_KEY: &str = "admin";
 const METADATA_KEY: &str = "metadata";
 const TOTAL_SUPPLY_KEY: &str = "total_supply";
+const TOTAL_ACCRUED_YIELD_KEY: &str = "total_accrued_yield";
+const TOTAL_SHARE_CLAIMS_KEY: &str = "total_share_claims";
 
 // Admin functions
 pub fn set_admin(env: &Env, admin: &Address) {
@@ -58,44 +73,130 @@ pub fn get_total_supply(env: &Env) -> i128 {
         .unwrap_or(0)
 }
 
-// User balance
-pub fn set_balance(env: &Env, address: &Address, balance: i128) {
+// Sum of every user's accrued yield, kept in step with each user's entry
+pub fn set_total_accrued_yield(env: &Env, amount: i128) {
+    env.storage().instance().set(&TOTAL_ACCRUED_YIELD_KEY, &amount);
+}
+
+pub fn get_total_accrued_yield(env: &Env) -> i128 {
     env.storage()
-        .persistent()
-        .set(&DataKey::Balance(address.clone()), &balance);
+        .instance()
+        .get(&TOTAL_ACCRUED_YIELD_KEY)
+        .unwrap_or(0)
 }
 
-pub fn get_balance(env: &Env, address: &Address) -> i128 {
+// Sum over holders of balance * SHARE_CLAIM_PRECISION / user_index, the vault shares each
+// balance stood for at its holder's index
+pub fn set_total_share_claims(env: &Env, amount: &I256) {
+    env.storage().instance().set(&TOTAL_SHARE_CLAIMS_KEY, amount);
+}
+
+pub fn get_total_share_claims(env: &Env) -> I256 {
+    env.storage()
+        .instance()
+        .get(&TOTAL_SHARE_CLAIMS_KEY)
+        .unwrap_or(I256::from_i32(env, 0))
+}
+
+// Per-user persistent entries are bumped on every read and write so
+// balances and unclaimed yield survive long gaps between interactions
+fn read_user_entry(env: &Env, key: &DataKey) -> i128 {
+    if let Some(value) = env.storage().persistent().get::<DataKey, i128>(key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+        value
+    } else {
+        0
+    }
+}
+
+fn write_user_entry(env: &Env, key: &DataKey, value: i128) {
+    env.storage().persistent().set(key, &value);
     env.storage()
         .persistent()
-        .get(&DataKey::Balance(address.clone()))
-        .unwrap_or(0)
+        .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
+
+// User balance
+pub fn set_balance(env: &Env, address: &Address, balance: i128) {
+    write_user_entry(env, &DataKey::Balance(address.clone()), balance);
+}
+
+pub fn get_balance(env: &Env, address: &Address) -> i128 {
+    read_user_entry(env, &DataKey::Balance(address.clone()))
 }
 
 // User index (exchange rate at last interaction)
 pub fn set_user_index(env: &Env, address: &Address, index: i128) {
-    env.storage()
-        .persistent()
-        .set(&DataKey::UserIndex(address.clone()), &index);
+    write_user_entry(env, &DataKey::UserIndex(address.clone()), index);
 }
 
 pub fn get_user_index(env: &Env, address: &Address) -> i128 {
-    env.storage()
-        .persistent()
-        .get(&DataKey::UserIndex(address.clone()))
-        .unwrap_or(0)
+    read_user_entry(env, &DataKey::UserIndex(address.clone()))
 }
 
 // Accrued yield (accumulated yield not yet claimed)
 pub fn set_accrued_yield(env: &Env, address: &Address, amount: i128) {
-    env.storage()
-        .persistent()
-        .set(&DataKey::AccruedYield(address.clone()), &amount);
+    write_user_entry(env, &DataKey::AccruedYield(address.clone()), amount);
 }
 
 pub fn get_accrued_yield(env: &Env, address: &Address) -> i128 {
-    env.storage()
-        .persistent()
-        .get(&DataKey::AccruedYield(address.clone()))
-        .unwrap_or(0)
-}
\ No newline at end of file
+    read_user_entry(env, &DataKey::AccruedYield(address.clone()))
+}
+
+// Allowance (temporary storage, zero once past the expiration ledger)
+pub fn get_allowance(env: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = DataKey::Allowance(from.clone(), spender.clone());
+    match env.storage().temporary().get::<DataKey, AllowanceValue>(&key) {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+        Some(allowance) => AllowanceValue {
+            amount: 0,
+            expiration_ledger: allowance.expiration_ledger,
+        },
+        None => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+pub fn set_allowance(
+    env: &Env,
+    from: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+) {
+    if amount > 0 && expiration_ledger < env.ledger().sequence() {
+        panic!("Expiration ledger is in the past");
+    }
+
+    let key = DataKey::Allowance(from.clone(), spender.clone());
+    let allowance = AllowanceValue {
+        amount,
+        expiration_ledger,
+    };
+    env.storage().temporary().set(&key, &allowance);
+
+    if amount > 0 {
+        let live_for = expiration_ledger - env.ledger().sequence();
+        env.storage().temporary().extend_ttl(&key, live_for, live_for);
+    }
+}
+
+pub fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+    let allowance = get_allowance(env, from, spender);
+    if allowance.amount < amount {
+        panic!("Insufficient allowance");
+    }
+    if amount > 0 {
+        set_allowance(
+            env,
+            from,
+            spender,
+            allowance.amount - amount,
+            allowance.expiration_ledger,
+        );
+    }
+}
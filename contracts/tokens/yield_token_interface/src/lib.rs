@@ -9,7 +9,16 @@ pub use soroban_sdk::token::TokenInterface as YieldTokenInterface;
 pub trait YieldTokenCustomTrait {
     fn __constructor(env: Env, admin: Address, decimal: u32, name: String, symbol: String);
     fn mint(env: Env, to: Address, amount: i128, exchange_rate: i128);
+    fn burn_with_rate(env: Env, from: Address, amount: i128, exchange_rate: i128);
+    fn total_supply(env: Env) -> i128;
     fn user_index(env: Env, address: Address) -> i128;
     fn accrued_yield(env: Env, address: Address) -> i128;
+    fn total_unclaimed_yield(env: Env) -> i128;
+    fn total_unclaimed_yield_with_rate(env: Env, exchange_rate: i128) -> i128;
+    fn claimable_yield(env: Env, user: Address) -> i128;
+    fn dry_run_claim(env: Env, user: Address) -> i128;
+    fn checkpoint(env: Env, user: Address) -> i128;
     fn claim_yield(env: Env, user: Address) -> i128;
+    fn claim_yield_as_assets(env: Env, user: Address) -> i128;
+    fn take_accrued_yield(env: Env, user: Address, max_amount: i128, exchange_rate: i128) -> i128;
 }
\ No newline at end of file
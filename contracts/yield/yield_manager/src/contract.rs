@@ -1,14 +1,19 @@
-use soroban_sdk::{token, Address, Env};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    token, vec, Address, Env, IntoVal, String, Symbol,
+};
 use crate::storage;
 use vault_interface::VaultContractClient;
 use defindex_interface::DefindexVaultContractClient;
-use yield_manager_interface::{YieldManagerTrait, VaultType};
+use yield_manager_interface::{YieldManagerTrait, VaultType, Summary};
 use principal_token_interface::PrincipalTokenClient;
 use yield_token_interface::YieldTokenCustomClient;
 
 #[cfg(feature = "contract")]
 use soroban_sdk::{contract, contractimpl};
 
+const MAX_FEE_BPS: u32 = 10_000;
+
 #[cfg(feature = "contract")]
 #[contract]
 pub struct YieldManager;
@@ -19,20 +24,218 @@ impl YieldManager {
     fn get_vault_exchange_rate(env: &Env) -> i128 {
         let vault_addr = storage::get_vault(env);
         let vault_type = storage::get_vault_type(env);
+        let rate_scale = storage::get_rate_scale(env);
 
         match vault_type {
             VaultType::Vault4626 => {
                 let client = VaultContractClient::new(env, &vault_addr);
-                client.convert_to_assets(&1i128)
+                client.convert_to_assets(&rate_scale)
             }
             VaultType::VaultDefindex => {
                 let client = DefindexVaultContractClient::new(env, &vault_addr);
-                let asset_amounts = client.get_asset_amounts_per_shares(&1i128);
+                let asset_amounts = client.get_asset_amounts_per_shares(&rate_scale);
                 asset_amounts.get(0).unwrap()
             }
         }
     }
 
+    // Rate the next update would store: the high-water mark of the stored and live vault rates,
+    // or once matured the rate it would lock at. Does not write to storage
+    fn compute_exchange_rate(env: &Env) -> i128 {
+        let stored_rate = storage::get_exchange_rate(env);
+        if storage::is_rate_locked(env) {
+            return stored_rate;
+        }
+
+        let vault_rate = YieldManager::get_vault_exchange_rate(env);
+        let high_water_mark = if vault_rate > stored_rate {
+            vault_rate
+        } else {
+            stored_rate
+        };
+        if !YieldManager::matured(env) {
+            return high_water_mark;
+        }
+
+        // The vault wasn't read between the last observation and now, so the growth over that
+        // gap is spread evenly and only the part up to maturity counts. However long the lock
+        // is put off this stays between the last observed rate and the current vault rate,
+        // and a vault growing steadily locks at its maturity rate whenever the call lands
+        let observed_at = storage::get_rate_observed_at(env);
+        let now = env.ledger().timestamp();
+        let until_maturity = (storage::get_maturity(env) - observed_at) as i128;
+        stored_rate + (high_water_mark - stored_rate) * until_maturity / (now - observed_at) as i128
+    }
+
+    // PT and YT minted for a deposit of vault shares (shares * exchange_rate)
+    fn calculate_mint_amount(shares_amount: i128, exchange_rate: i128) -> i128 {
+        if exchange_rate <= 0 {
+            panic!("Exchange rate must be positive");
+        }
+
+        shares_amount
+            .checked_mul(exchange_rate)
+            .expect("mint amount overflow")
+    }
+
+    // Moves the principal share count by `delta` shares entering (+) or leaving (-) principal
+    fn adjust_total_principal_shares(env: &Env, delta: i128) {
+        let total = storage::get_total_principal_shares(env)
+            .checked_add(delta)
+            .expect("principal shares overflow");
+        storage::set_total_principal_shares(env, total);
+    }
+
+    // Records `shares` deposited by `user`, reverting past the per-user cap
+    fn record_user_deposit(env: &Env, user: &Address, shares: i128) {
+        let deposited = storage::get_user_deposited(env, user)
+            .checked_add(shares)
+            .expect("deposit overflow");
+        let cap = storage::get_per_user_cap(env);
+        if cap > 0 && deposited > cap {
+            panic!("per-user cap exceeded");
+        }
+        storage::set_user_deposited(env, user, deposited);
+    }
+
+    // Frees `user`'s cap in proportion to the `pt_burned` out of the `pt_balance` PT they held.
+    // The shares paid out depend on how far the rate moved since the deposit, the PT doesn't,
+    // so burning all of it frees the whole tally. Rounds up so the sub-share PT dust an exit
+    // leaves behind doesn't pin a share of the cap
+    fn record_user_redemption(env: &Env, user: &Address, pt_burned: i128, pt_balance: i128) {
+        let deposited = storage::get_user_deposited(env, user);
+        if deposited == 0 || pt_balance <= 0 {
+            return;
+        }
+
+        let freed = if pt_burned >= pt_balance {
+            deposited
+        } else {
+            let scaled = deposited
+                .checked_mul(pt_burned)
+                .expect("deposit overflow");
+            (scaled + pt_balance - 1) / pt_balance
+        };
+        storage::set_user_deposited(env, user, deposited - freed);
+    }
+
+    // Sends the protocol fee (if any) on a yield payout to the fee recipient, returns the net amount
+    fn take_fee(env: &Env, vault_token_client: &token::Client, amount: i128) -> i128 {
+        let fee_bps = storage::get_fee_bps(env);
+        if fee_bps == 0 {
+            return amount;
+        }
+
+        let fee_amount = amount
+            .checked_mul(fee_bps as i128)
+            .expect("fee amount overflow")
+            / MAX_FEE_BPS as i128;
+        if fee_amount == 0 {
+            return amount;
+        }
+
+        let fee_recipient = storage::get_fee_recipient(env).expect("Fee recipient not set");
+        vault_token_client.transfer(&env.current_contract_address(), &fee_recipient, &fee_amount);
+        amount - fee_amount
+    }
+
+    // Shares held on behalf of PT holders at `exchange_rate`: the balance less the yield
+    // still owed to YT holders, never more than the principal deposited
+    fn principal_backing(env: &Env, exchange_rate: i128) -> i128 {
+        let vault_addr = storage::get_vault(env);
+        let yt_addr = storage::get_yield_token(env);
+
+        let balance = token::Client::new(env, &vault_addr).balance(&env.current_contract_address());
+        let unclaimed_yield =
+            YieldTokenCustomClient::new(env, &yt_addr).total_unclaimed_yield_with_rate(&exchange_rate);
+        (balance - unclaimed_yield)
+            .min(storage::get_total_principal_shares(env))
+            .max(0)
+    }
+
+    // Shares paid for `pt_amount` PT at the rate locked at maturity, and the PT burned.
+    // If the principal backing is short of what all outstanding PT is owed (the vault lost
+    // value held by the manager), paying in full would drain it first come first served.
+    // Instead every PT redeems for its pro-rata part of the backing. Unclaimed YT yield is
+    // not part of the backing, so the haircut is the same whether YT holders claim first
+    fn principal_redemption(env: &Env, pt_amount: i128) -> (i128, i128) {
+        let exchange_rate = YieldManager::compute_exchange_rate(env);
+        let pt_addr = storage::get_principal_token(env);
+
+        let backing = YieldManager::principal_backing(env, exchange_rate);
+        let pt_supply = PrincipalTokenClient::new(env, &pt_addr).total_supply();
+        if pt_supply / exchange_rate > backing {
+            let shares = pt_amount
+                .checked_mul(backing)
+                .expect("haircut overflow")
+                / pt_supply;
+            (shares, pt_amount)
+        } else {
+            // Only burn the PT backing whole shares, the remainder stays with the user
+            let shares = pt_amount / exchange_rate;
+            (shares, shares * exchange_rate)
+        }
+    }
+
+    // Redeems `from`'s PT for vault shares sent to `to` after maturity, the caller has already
+    // authorized `from`
+    fn redeem_principal_for(
+        env: &Env,
+        from: &Address,
+        to: &Address,
+        pt_amount: i128,
+        min_shares_out: i128,
+    ) -> i128 {
+        if pt_amount <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        YieldManager::require_matured(env);
+        YieldManager::update_exchange_rate(env);
+
+        let vault_addr = storage::get_vault(env);
+        let pt_addr = storage::get_principal_token(env);
+
+        let vault_token_client = token::Client::new(env, &vault_addr);
+        let (shares_to_return, pt_consumed) = YieldManager::principal_redemption(env, pt_amount);
+        if shares_to_return < min_shares_out {
+            panic!("slippage");
+        }
+
+        // Burn PT tokens from user
+        let pt_token_client = token::Client::new(env, &pt_addr);
+        let pt_balance = pt_token_client.balance(from);
+        pt_token_client.burn(from, &pt_consumed);
+
+        // Transfer vault shares to the receiver
+        vault_token_client.transfer(
+            &env.current_contract_address(),
+            to,
+            &shares_to_return,
+        );
+        YieldManager::adjust_total_principal_shares(env, -shares_to_return);
+        YieldManager::record_user_redemption(env, from, pt_consumed, pt_balance);
+
+        shares_to_return
+    }
+
+    // Maturity is reached once the ledger timestamp is at or past the stored maturity
+    fn matured(env: &Env) -> bool {
+        env.ledger().timestamp() >= storage::get_maturity(env)
+    }
+
+    fn require_matured(env: &Env) {
+        if !YieldManager::matured(env) {
+            panic!("Maturity not reached");
+        }
+    }
+
+    fn require_not_matured(env: &Env) {
+        if YieldManager::matured(env) {
+            panic!("Maturity reached");
+        }
+    }
+
     // Update maturity before maturity (exchange rate for users locks after maturity)
     // Rate can only increase
     fn update_exchange_rate(env: &Env) {
@@ -40,23 +243,18 @@ impl YieldManager {
             return;
         }
 
-        let maturity = storage::get_maturity(env);
-        let current_time = env.ledger().timestamp();
-
-        // Get current vault rate using the helper function
-        let new_rate = YieldManager::get_vault_exchange_rate(env);
-
-        // Get the currently stored rate
-        let stored_rate = storage::get_exchange_rate(env);
+        // Get the rate after applying the high-water mark, or the maturity rate once matured
+        let new_rate = YieldManager::compute_exchange_rate(env);
 
         // Only update if the new rate is higher
-        if new_rate > stored_rate {
+        if new_rate > storage::get_exchange_rate(env) {
             storage::set_exchange_rate(env, new_rate);
         }
 
-        // If we've reached or passed maturity, lock the rate
-        if current_time >= maturity {
+        if YieldManager::matured(env) {
             storage::set_rate_locked(env);
+        } else {
+            storage::set_rate_observed_at(env, env.ledger().timestamp());
         }
     }
 }
@@ -70,15 +268,31 @@ impl YieldManagerTrait for YieldManager {
         vault: Address,
         vault_type: VaultType,
         maturity: u64,
+        rate_scale: i128,
     ) {
+        if rate_scale <= 0 {
+            panic!("Rate scale must be positive");
+        }
+
+        // A maturity already reached would lock the rate on first use
+        if maturity == 0 || maturity <= env.ledger().timestamp() {
+            panic!("Maturity must be in the future");
+        }
+
         storage::set_admin(&env, &admin);
         storage::set_vault(&env, &vault);
         storage::set_vault_type(&env, vault_type);
         storage::set_maturity(&env, maturity);
+        storage::set_rate_scale(&env, rate_scale);
 
         // Fetch and store the initial exchange rate from the vault using the helper function
         let initial_rate = YieldManager::get_vault_exchange_rate(&env);
+        if initial_rate <= 0 {
+            panic!("Vault exchange rate must be positive");
+        }
         storage::set_exchange_rate(&env, initial_rate);
+        storage::set_start(&env, initial_rate, env.ledger().timestamp());
+        storage::set_rate_observed_at(&env, env.ledger().timestamp());
     }
 
     fn set_token_contracts(env: Env, pt_addr: Address, yt_addr: Address) {
@@ -90,15 +304,188 @@ impl YieldManagerTrait for YieldManager {
             panic!("Token contracts already initialized");
         }
 
+        // Minting PT and YT to one contract, or to a contract the manager also holds
+        // or pays out, would silently corrupt its balances
+        if pt_addr == yt_addr {
+            panic!("PT and YT must be different contracts");
+        }
+        let vault_addr = storage::get_vault(&env);
+        // Read through the vault rather than the cache, which is still empty on a fresh deploy
+        let underlying_addr = Self::get_underlying_asset(env.clone());
+        let this = env.current_contract_address();
+        for token_addr in [&pt_addr, &yt_addr] {
+            if *token_addr == vault_addr
+                || *token_addr == underlying_addr
+                || *token_addr == this
+            {
+                panic!("Invalid token contract");
+            }
+        }
+
+        // PT/YT amounts are shares * exchange_rate, where the rate is the asset units behind
+        // rate_scale share units. A PT unit is therefore an asset unit carried at rate_scale
+        // extra precision, so the tokens need the asset's decimals plus log10(rate_scale).
+        // The share decimals cancel out, only the vault's asset decimals matter
+        let pt_decimals = token::Client::new(&env, &pt_addr).decimals();
+        let yt_decimals = token::Client::new(&env, &yt_addr).decimals();
+        let asset_decimals = token::Client::new(&env, &underlying_addr).decimals();
+        let rate_scale = storage::get_rate_scale(&env);
+        let expected_decimals = rate_scale
+            .checked_ilog10()
+            .filter(|d| 10i128.checked_pow(*d) == Some(rate_scale))
+            .and_then(|d| asset_decimals.checked_add(d));
+        if pt_decimals != yt_decimals || expected_decimals != Some(pt_decimals) {
+            panic!(
+                "decimal/scale mismatch: pt decimals {}, yt decimals {}, asset decimals {}, rate scale {}",
+                pt_decimals, yt_decimals, asset_decimals, rate_scale
+            );
+        }
+
         storage::set_principal_token(&env, &pt_addr);
         storage::set_yield_token(&env, &yt_addr);
+        storage::set_token_decimals(&env, pt_decimals);
         storage::set_initialized(&env);
     }
 
+    fn propose_admin(env: Env, new_admin: Address) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        storage::set_pending_admin(&env, &new_admin);
+    }
+
+    fn accept_admin(env: Env) {
+        let pending_admin = storage::get_pending_admin(&env).expect("No pending admin");
+        pending_admin.require_auth();
+
+        storage::set_admin(&env, &pending_admin);
+        storage::remove_pending_admin(&env);
+    }
+
+    fn get_admin(env: Env) -> Address {
+        storage::get_admin(&env)
+    }
+
+    fn get_pending_admin(env: Env) -> Option<Address> {
+        storage::get_pending_admin(&env)
+    }
+
+    fn is_initialized(env: Env) -> bool {
+        storage::is_initialized(&env)
+    }
+
+    fn pause(env: Env) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        storage::set_paused(&env, true);
+    }
+
+    fn unpause(env: Env) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        storage::set_paused(&env, false);
+    }
+
+    fn is_paused(env: Env) -> bool {
+        storage::is_paused(&env)
+    }
+
+    // The PT's administrator is this contract, so its admin calls are reached through here
+    fn pause_principal_token(env: Env) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        let pt_addr = storage::get_principal_token(&env);
+        PrincipalTokenClient::new(&env, &pt_addr).pause();
+    }
+
+    fn unpause_principal_token(env: Env) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        let pt_addr = storage::get_principal_token(&env);
+        PrincipalTokenClient::new(&env, &pt_addr).unpause();
+    }
+
+    fn set_principal_token_metadata(env: Env, name: String, symbol: String) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        let pt_addr = storage::get_principal_token(&env);
+        PrincipalTokenClient::new(&env, &pt_addr).set_metadata(&name, &symbol);
+    }
+
+    fn set_fee(env: Env, fee_bps: u32, fee_recipient: Address) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        if fee_bps > MAX_FEE_BPS {
+            panic!("Fee exceeds maximum");
+        }
+
+        storage::set_fee(&env, fee_bps, &fee_recipient);
+    }
+
+    fn get_fee_bps(env: Env) -> u32 {
+        storage::get_fee_bps(&env)
+    }
+
+    fn get_fee_recipient(env: Env) -> Option<Address> {
+        storage::get_fee_recipient(&env)
+    }
+
+    fn set_per_user_cap(env: Env, cap: i128) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        if cap < 0 {
+            panic!("Cap must not be negative");
+        }
+
+        storage::set_per_user_cap(&env, cap);
+    }
+
+    fn get_per_user_cap(env: Env) -> i128 {
+        storage::get_per_user_cap(&env)
+    }
+
+    fn get_user_deposited(env: Env, user: Address) -> i128 {
+        storage::get_user_deposited(&env, &user)
+    }
+
     fn get_vault(env: Env) -> Address {
         storage::get_vault(&env)
     }
 
+    fn get_vault_type(env: Env) -> VaultType {
+        storage::get_vault_type(&env)
+    }
+
+    fn get_vault_info(env: Env) -> (Address, VaultType) {
+        (storage::get_vault(&env), storage::get_vault_type(&env))
+    }
+
+    fn get_underlying_asset(env: Env) -> Address {
+        if let Some(asset) = storage::get_underlying_asset(&env) {
+            return asset;
+        }
+
+        let vault_addr = storage::get_vault(&env);
+        let asset = match storage::get_vault_type(&env) {
+            VaultType::Vault4626 => VaultContractClient::new(&env, &vault_addr).query_asset(),
+            VaultType::VaultDefindex => {
+                // Single-asset vaults, matching how the exchange rate is read
+                let assets = DefindexVaultContractClient::new(&env, &vault_addr).get_assets();
+                assets.get(0).expect("Vault has no assets").address
+            }
+        };
+
+        storage::set_underlying_asset(&env, &asset);
+        asset
+    }
+
     fn get_principal_token(env: Env) -> Address {
         storage::get_principal_token(&env)
     }
@@ -107,10 +494,81 @@ impl YieldManagerTrait for YieldManager {
         storage::get_yield_token(&env)
     }
 
+    fn get_token_contracts(env: Env) -> (Address, Address) {
+        (
+            storage::get_principal_token(&env),
+            storage::get_yield_token(&env),
+        )
+    }
+
     fn get_maturity(env: Env) -> u64 {
         storage::get_maturity(&env)
     }
 
+    fn is_matured(env: Env) -> bool {
+        YieldManager::matured(&env)
+    }
+
+    fn is_rate_locked(env: Env) -> bool {
+        storage::is_rate_locked(&env)
+    }
+
+    fn lock_rate(env: Env) -> i128 {
+        // Permissionless: anyone can lock the rate once maturity has passed. Same rule as every
+        // other rate update whichever call comes first: the vault's growth since the last
+        // observation, prorated up to maturity, see compute_exchange_rate
+        YieldManager::require_matured(&env);
+        YieldManager::update_exchange_rate(&env);
+
+        storage::get_exchange_rate(&env)
+    }
+
+    fn extend_maturity(env: Env, new_maturity: u64) {
+        let admin = storage::get_admin(&env);
+        admin.require_auth();
+
+        // Once maturity is reached the rate is (or is about to be) locked for redemptions
+        let maturity = storage::get_maturity(&env);
+        if storage::is_rate_locked(&env) || YieldManager::matured(&env) {
+            panic!("Rate already locked");
+        }
+
+        if new_maturity <= maturity {
+            panic!("New maturity must be later than current maturity");
+        }
+
+        storage::set_maturity(&env, new_maturity);
+    }
+
+    fn get_rate_scale(env: Env) -> i128 {
+        storage::get_rate_scale(&env)
+    }
+
+    fn get_token_decimals(env: Env) -> u32 {
+        storage::get_token_decimals(&env)
+    }
+
+    fn total_principal_shares(env: Env) -> i128 {
+        storage::get_total_principal_shares(&env)
+    }
+
+    fn get_summary(env: Env) -> Summary {
+        let pt_addr = storage::get_principal_token(&env);
+        let yt_addr = storage::get_yield_token(&env);
+        let vault_addr = storage::get_vault(&env);
+
+        Summary {
+            maturity: storage::get_maturity(&env),
+            stored_exchange_rate: storage::get_exchange_rate(&env),
+            rate_locked: storage::is_rate_locked(&env),
+            paused: storage::is_paused(&env),
+            total_pt_supply: PrincipalTokenClient::new(&env, &pt_addr).total_supply(),
+            total_yt_supply: YieldTokenCustomClient::new(&env, &yt_addr).total_supply(),
+            backing_shares: token::Client::new(&env, &vault_addr)
+                .balance(&env.current_contract_address()),
+        }
+    }
+
     fn get_exchange_rate(env: Env) -> i128 {
         // Update the stored exchange rate (if before maturity)
         YieldManager::update_exchange_rate(&env);
@@ -118,13 +576,96 @@ impl YieldManagerTrait for YieldManager {
         storage::get_exchange_rate(&env)
     }
 
-    fn deposit(env: Env, from: Address, shares_amount: i128) {
+    fn peek_exchange_rate(env: Env) -> i128 {
+        // What get_exchange_rate would return, without writing to storage
+        YieldManager::compute_exchange_rate(&env)
+    }
+
+    fn preview_deposit(env: Env, shares_amount: i128) -> (i128, i128) {
+        // Same rate and math as deposit, without updating the stored rate
+        let exchange_rate = YieldManager::compute_exchange_rate(&env);
+        let mint_amount = YieldManager::calculate_mint_amount(shares_amount, exchange_rate);
+        (mint_amount, mint_amount)
+    }
+
+    fn preview_redeem(env: Env, pt_amount: i128) -> i128 {
+        // Same rate and math as redeem_principal, including the haircut when underwater
+        let (shares, _) = YieldManager::principal_redemption(&env, pt_amount);
+        shares
+    }
+
+    fn pt_underlying_value(env: Env, user: Address) -> i128 {
+        let pt_addr = storage::get_principal_token(&env);
+        let pt_balance = token::Client::new(&env, &pt_addr).balance(&user);
+
+        // PT converts back to whole shares at the rate, shares are valued at the same rate
+        let exchange_rate = YieldManager::compute_exchange_rate(&env);
+        let shares = pt_balance / exchange_rate;
+        shares * exchange_rate / storage::get_rate_scale(&env)
+    }
+
+    fn pt_price(env: Env) -> i128 {
+        // Underlying per whole PT (10^decimals units), scaled by the rate scale.
+        // PT redeems at the locked rate (or the rate it would lock at now) into
+        // shares valued at the live vault rate: par before maturity, then it
+        // tracks the vault's growth since the lock
+        let redeem_rate = YieldManager::compute_exchange_rate(&env);
+        let vault_rate = YieldManager::get_vault_exchange_rate(&env);
+        vault_rate
+            .checked_mul(storage::get_rate_scale(&env))
+            .expect("price overflow")
+            / redeem_rate
+    }
+
+    fn yt_price(env: Env) -> i128 {
+        // Underlying per whole YT (10^decimals units), scaled by the rate scale.
+        // Yield already accrued stays with the holder it accrued to, so a YT is worth the
+        // yield still ahead of it: the rate growth left until maturity, per unit of the
+        // current rate. That growth is projected from the vault's realized growth since
+        // deployment, so the price doesn't depend on when the rate was last stored. Zero
+        // from maturity on since YT stops accruing
+        if storage::is_rate_locked(&env) || YieldManager::matured(&env) {
+            return 0;
+        }
+
+        let (start_rate, start_time) = storage::get_start(&env);
+        let now = env.ledger().timestamp();
+        if now <= start_time {
+            return 0;
+        }
+        let elapsed = (now - start_time) as i128;
+        let remaining = (storage::get_maturity(&env) - now) as i128;
+
+        let current_rate = YieldManager::compute_exchange_rate(&env);
+        (current_rate - start_rate)
+            .checked_mul(remaining)
+            .and_then(|growth| growth.checked_mul(storage::get_rate_scale(&env)))
+            .expect("price overflow")
+            / elapsed
+            / current_rate
+    }
+
+    fn deposit(
+        env: Env,
+        from: Address,
+        shares_amount: i128,
+        min_pt_out: i128,
+        min_yt_out: i128,
+    ) -> (i128, i128) {
         from.require_auth();
 
+        // Only new deposits are halted, redemptions and yield claims stay open
+        if storage::is_paused(&env) {
+            panic!("contract paused");
+        }
+
         if shares_amount <= 0 {
             panic!("Amount must be positive");
         }
 
+        // PT minted after maturity would redeem at the locked rate immediately
+        YieldManager::require_not_matured(&env);
+
         // Update the stored exchange rate (if before maturity)
         YieldManager::update_exchange_rate(&env);
 
@@ -136,11 +677,95 @@ impl YieldManagerTrait for YieldManager {
         let exchange_rate = storage::get_exchange_rate(&env);
 
         // Calculate the amount of tokens to mint based on shares and exchange rate
-        let mint_amount = shares_amount * exchange_rate;
+        let mint_amount = YieldManager::calculate_mint_amount(shares_amount, exchange_rate);
+
+        // PT and YT mint the same amount today, both bounds are kept in case they diverge
+        if mint_amount < min_pt_out || mint_amount < min_yt_out {
+            panic!("slippage");
+        }
 
         // Transfer vault shares from user to yield manager
         let vault_token_client = token::Client::new(&env, &vault_addr);
-        vault_token_client.transfer(&from, &env.current_contract_address(), &shares_amount);
+        vault_token_client.transfer(&from, env.current_contract_address(), &shares_amount);
+        YieldManager::adjust_total_principal_shares(&env, shares_amount);
+        YieldManager::record_user_deposit(&env, &from, shares_amount);
+
+        // Mint PT tokens to user (shares * exchange_rate) using type-safe client
+        let pt_client = PrincipalTokenClient::new(&env, &pt_addr);
+        pt_client.mint(&from, &mint_amount);
+
+        // Mint YT tokens to user (shares * exchange_rate) using type-safe client
+        let yt_client = YieldTokenCustomClient::new(&env, &yt_addr);
+        yt_client.mint(&from, &mint_amount, &exchange_rate);
+
+        (mint_amount, mint_amount)
+    }
+
+    fn deposit_underlying(
+        env: Env,
+        from: Address,
+        assets: i128,
+        min_pt_out: i128,
+        min_yt_out: i128,
+    ) -> (i128, i128) {
+        from.require_auth();
+
+        if storage::is_paused(&env) {
+            panic!("contract paused");
+        }
+
+        if assets <= 0 {
+            panic!("Amount must be positive");
+        }
+
+        // Only the 4626 vault interface exposes deposit
+        if storage::get_vault_type(&env) != VaultType::Vault4626 {
+            panic!("vault type not supported");
+        }
+
+        YieldManager::require_not_matured(&env);
+
+        // Update the stored exchange rate (if before maturity)
+        YieldManager::update_exchange_rate(&env);
+
+        let vault_addr = storage::get_vault(&env);
+        let pt_addr = storage::get_principal_token(&env);
+        let yt_addr = storage::get_yield_token(&env);
+        let vault_client = VaultContractClient::new(&env, &vault_addr);
+        let this = env.current_contract_address();
+
+        // Pull the underlying to the manager, then deposit it so the vault mints the shares here
+        let asset_addr = vault_client.query_asset();
+        token::Client::new(&env, &asset_addr).transfer(&from, &this, &assets);
+
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: asset_addr,
+                    fn_name: Symbol::new(&env, "transfer"),
+                    args: (this.clone(), vault_addr.clone(), assets).into_val(&env),
+                },
+                sub_invocations: vec![&env],
+            }),
+        ]);
+        let shares_amount = vault_client.deposit(&assets, &this, &this, &this);
+        if shares_amount <= 0 {
+            panic!("Amount must be positive");
+        }
+        YieldManager::adjust_total_principal_shares(&env, shares_amount);
+        YieldManager::record_user_deposit(&env, &from, shares_amount);
+
+        // Get the stored exchange rate
+        let exchange_rate = storage::get_exchange_rate(&env);
+
+        // Calculate the amount of tokens to mint based on shares and exchange rate
+        let mint_amount = YieldManager::calculate_mint_amount(shares_amount, exchange_rate);
+
+        // The bounds cover the vault's share price as well as the manager's rate
+        if mint_amount < min_pt_out || mint_amount < min_yt_out {
+            panic!("slippage");
+        }
 
         // Mint PT tokens to user (shares * exchange_rate) using type-safe client
         let pt_client = PrincipalTokenClient::new(&env, &pt_addr);
@@ -149,54 +774,116 @@ impl YieldManagerTrait for YieldManager {
         // Mint YT tokens to user (shares * exchange_rate) using type-safe client
         let yt_client = YieldTokenCustomClient::new(&env, &yt_addr);
         yt_client.mint(&from, &mint_amount, &exchange_rate);
+
+        (mint_amount, mint_amount)
     }
 
-    fn distribute_yield(env: Env, to: Address, shares_amount: i128) {
+    fn distribute_yield(env: Env, to: Address, shares_amount: i128) -> i128 {
         // Only the YT contract can call this
         let yt_addr = storage::get_yield_token(&env);
         yt_addr.require_auth();
 
         if shares_amount <= 0 {
-            return;
+            return 0;
         }
 
         // Update the stored exchange rate (if before maturity)
         YieldManager::update_exchange_rate(&env);
 
-        // Transfer vault shares from yield manager to user
         let vault_addr = storage::get_vault(&env);
         let vault_token_client = token::Client::new(&env, &vault_addr);
+
+        // Never pay out more than the manager holds, so rounding can't lock claims
+        let manager_balance = vault_token_client.balance(&env.current_contract_address());
+        let distributed = shares_amount.min(manager_balance);
+        if distributed <= 0 {
+            return 0;
+        }
+
+        // Skim the protocol fee (if any) before paying out the user
+        let user_amount = YieldManager::take_fee(&env, &vault_token_client, distributed);
+
+        // Transfer the remaining vault shares from yield manager to user
         vault_token_client.transfer(
             &env.current_contract_address(),
             &to,
-            &shares_amount,
+            &user_amount,
         );
+
+        distributed
+    }
+
+    fn compound(env: Env, user: Address) -> (i128, i128) {
+        user.require_auth();
+
+        // Compounding mints new PT/YT, so it is halted along with deposits
+        if storage::is_paused(&env) {
+            panic!("contract paused");
+        }
+
+        // PT minted after maturity would redeem at the locked rate immediately
+        YieldManager::require_not_matured(&env);
+
+        // Update the stored exchange rate (if before maturity)
+        YieldManager::update_exchange_rate(&env);
+        let exchange_rate = storage::get_exchange_rate(&env);
+
+        let vault_addr = storage::get_vault(&env);
+        let pt_addr = storage::get_principal_token(&env);
+        let yt_addr = storage::get_yield_token(&env);
+        let vault_token_client = token::Client::new(&env, &vault_addr);
+
+        // The YT can't call back into distribute_yield while we're in a call, so it hands
+        // over the accrued yield instead. The shares never leave the manager: they're
+        // re-deposited in place, capped at the balance like a regular claim
+        let manager_balance = vault_token_client.balance(&env.current_contract_address());
+        let yt_client = YieldTokenCustomClient::new(&env, &yt_addr);
+        let taken = yt_client.take_accrued_yield(&user, &manager_balance, &exchange_rate);
+        if taken <= 0 {
+            return (0, 0);
+        }
+
+        // The compounded yield now backs principal
+        let shares_amount = YieldManager::take_fee(&env, &vault_token_client, taken);
+        let mint_amount = YieldManager::calculate_mint_amount(shares_amount, exchange_rate);
+        YieldManager::adjust_total_principal_shares(&env, shares_amount);
+        // The re-deposited shares count against the per-user cap like a deposit
+        YieldManager::record_user_deposit(&env, &user, shares_amount);
+
+        let pt_client = PrincipalTokenClient::new(&env, &pt_addr);
+        pt_client.mint(&user, &mint_amount);
+        yt_client.mint(&user, &mint_amount, &exchange_rate);
+
+        (mint_amount, mint_amount)
     }
 
-    fn redeem_principal(env: Env, from: Address, pt_amount: i128) {
+    fn redeem_combined(env: Env, from: Address, amount: i128) {
         from.require_auth();
 
-        if pt_amount <= 0 {
+        if amount <= 0 {
             panic!("Amount must be positive");
         }
 
-        // Check maturity has passed
-        let maturity = storage::get_maturity(&env);
-        let current_time = env.ledger().timestamp();
-        if current_time < maturity {
-            panic!("Maturity not reached");
-        }
+        // Update the stored exchange rate (if before maturity)
+        YieldManager::update_exchange_rate(&env);
 
         let vault_addr = storage::get_vault(&env);
         let pt_addr = storage::get_principal_token(&env);
+        let yt_addr = storage::get_yield_token(&env);
 
-        // Get the stored exchange rate (locked at maturity)
+        // The principal is paid as a PT redemption would, haircut included when the
+        // backing is short, and the same amount of YT is burned with it
         let exchange_rate = storage::get_exchange_rate(&env);
-        let shares_to_return = pt_amount / exchange_rate;
+        let (shares_to_return, amount_consumed) = YieldManager::principal_redemption(&env, amount);
 
         // Burn PT tokens from user
         let pt_token_client = token::Client::new(&env, &pt_addr);
-        pt_token_client.burn(&from, &pt_amount);
+        let pt_balance = pt_token_client.balance(&from);
+        pt_token_client.burn(&from, &amount_consumed);
+
+        // Burn YT tokens from user, outstanding yield is accrued first and stays claimable
+        let yt_client = YieldTokenCustomClient::new(&env, &yt_addr);
+        yt_client.burn_with_rate(&from, &amount_consumed, &exchange_rate);
 
         // Transfer vault shares back to user
         let vault_token_client = token::Client::new(&env, &vault_addr);
@@ -205,5 +892,44 @@ impl YieldManagerTrait for YieldManager {
             &from,
             &shares_to_return,
         );
+        YieldManager::adjust_total_principal_shares(&env, -shares_to_return);
+        YieldManager::record_user_redemption(&env, &from, amount_consumed, pt_balance);
+    }
+
+    fn redeem_principal(env: Env, from: Address, pt_amount: i128, min_shares_out: i128) -> i128 {
+        from.require_auth();
+
+        YieldManager::redeem_principal_for(&env, &from, &from, pt_amount, min_shares_out)
+    }
+
+    fn redeem_all(env: Env, from: Address) -> i128 {
+        from.require_auth();
+
+        let pt_addr = storage::get_principal_token(&env);
+        let pt_balance = token::Client::new(&env, &pt_addr).balance(&from);
+
+        YieldManager::redeem_principal_for(&env, &from, &from, pt_balance, 0)
+    }
+
+    fn redeem_principal_to_underlying(env: Env, from: Address, pt_amount: i128) -> i128 {
+        from.require_auth();
+
+        // Only the 4626 vault interface exposes redeem
+        if storage::get_vault_type(&env) != VaultType::Vault4626 {
+            panic!("vault type not supported");
+        }
+
+        // The shares come to the manager first and are redeemed on the user's behalf
+        let this = env.current_contract_address();
+        let shares = YieldManager::redeem_principal_for(&env, &from, &this, pt_amount, 0);
+
+        // PT below one share's worth burns nothing and pays nothing, skip the vault call
+        if shares > 0 {
+            // The vault rounds the assets down, the user receives what it actually pays
+            let vault_addr = storage::get_vault(&env);
+            VaultContractClient::new(&env, &vault_addr).redeem(&shares, &from, &this, &this)
+        } else {
+            0
+        }
     }
 }
\ No newline at end of file
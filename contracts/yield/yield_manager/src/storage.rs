@@ -1,16 +1,34 @@
 use soroban_sdk::{Address, Env};
 use yield_manager_interface::VaultType;
 
+// Storage TTL constants
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const USER_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const USER_LIFETIME_THRESHOLD: u32 = USER_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
 // Storage keys
 const ADMIN_KEY: &str = "admin";
+const PENDING_ADMIN_KEY: &str = "pending_admin";
 const VAULT_KEY: &str = "vault";
+const UNDERLYING_ASSET_KEY: &str = "underlying_asset";
 const VAULT_TYPE_KEY: &str = "vault_type";
 const PRINCIPAL_TOKEN_KEY: &str = "principal_token";
 const YIELD_TOKEN_KEY: &str = "yield_token";
 const MATURITY_KEY: &str = "maturity";
+const RATE_SCALE_KEY: &str = "rate_scale";
+const TOKEN_DECIMALS_KEY: &str = "token_decimals";
 const EXCHANGE_RATE_KEY: &str = "exchange_rate";
 const RATE_LOCKED_KEY: &str = "rate_locked";
+const RATE_OBSERVED_AT_KEY: &str = "rate_observed_at";
+const START_RATE_KEY: &str = "start_rate";
+const START_TIME_KEY: &str = "start_time";
 const INITIALIZED_KEY: &str = "initialized"; // TODO: redundant??
+const PAUSED_KEY: &str = "paused";
+const FEE_BPS_KEY: &str = "fee_bps";
+const FEE_RECIPIENT_KEY: &str = "fee_recipient";
+const TOTAL_PRINCIPAL_SHARES_KEY: &str = "total_principal_shares";
+const PER_USER_CAP_KEY: &str = "per_user_cap";
+const USER_DEPOSITED_KEY: &str = "user_deposited";
 
 // Admin functions
 pub fn set_admin(env: &Env, admin: &Address) {
@@ -24,6 +42,19 @@ pub fn get_admin(env: &Env) -> Address {
         .expect("Admin not set")
 }
 
+// Pending admin (set by propose_admin, cleared by accept_admin)
+pub fn set_pending_admin(env: &Env, pending_admin: &Address) {
+    env.storage().instance().set(&PENDING_ADMIN_KEY, pending_admin);
+}
+
+pub fn get_pending_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&PENDING_ADMIN_KEY)
+}
+
+pub fn remove_pending_admin(env: &Env) {
+    env.storage().instance().remove(&PENDING_ADMIN_KEY);
+}
+
 // Vault address (immutable after initialization)
 pub fn set_vault(env: &Env, vault: &Address) {
     env.storage().instance().set(&VAULT_KEY, vault);
@@ -45,6 +76,15 @@ pub fn get_vault_type(env: &Env) -> VaultType {
     env.storage().instance().get(&VAULT_TYPE_KEY).expect("Vault type not set")
 }
 
+// Vault's underlying asset (cached on first lookup, immutable)
+pub fn set_underlying_asset(env: &Env, asset: &Address) {
+    env.storage().instance().set(&UNDERLYING_ASSET_KEY, asset);
+}
+
+pub fn get_underlying_asset(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&UNDERLYING_ASSET_KEY)
+}
+
 // Maturity timestamp (immutable after initialization)
 pub fn set_maturity(env: &Env, maturity: u64) {
     env.storage().instance().set(&MATURITY_KEY, &maturity);
@@ -81,6 +121,72 @@ pub fn get_yield_token(env: &Env) -> Address {
         .expect("Yield token not set")
 }
 
+// Vault shares quoted when reading the exchange rate; the rate is assets per share scaled by this
+pub fn set_rate_scale(env: &Env, rate_scale: i128) {
+    env.storage().instance().set(&RATE_SCALE_KEY, &rate_scale);
+}
+
+pub fn get_rate_scale(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&RATE_SCALE_KEY)
+        .expect("Rate scale not set")
+}
+
+// PT/YT decimals, checked against the rate scale when the tokens are set
+pub fn set_token_decimals(env: &Env, decimals: u32) {
+    env.storage().instance().set(&TOKEN_DECIMALS_KEY, &decimals);
+}
+
+pub fn get_token_decimals(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&TOKEN_DECIMALS_KEY)
+        .expect("Token decimals not set")
+}
+
+// Vault shares deposited for PT/YT and not yet redeemed, the rest of the balance is yield
+pub fn set_total_principal_shares(env: &Env, shares: i128) {
+    env.storage().instance().set(&TOTAL_PRINCIPAL_SHARES_KEY, &shares);
+}
+
+pub fn get_total_principal_shares(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&TOTAL_PRINCIPAL_SHARES_KEY)
+        .unwrap_or(0)
+}
+
+// Max shares any one address may have deposited at a time, 0 when unlimited
+pub fn set_per_user_cap(env: &Env, cap: i128) {
+    env.storage().instance().set(&PER_USER_CAP_KEY, &cap);
+}
+
+pub fn get_per_user_cap(env: &Env) -> i128 {
+    env.storage().instance().get(&PER_USER_CAP_KEY).unwrap_or(0)
+}
+
+// Shares a user has deposited net of what they redeemed, bumped on every read and write
+pub fn set_user_deposited(env: &Env, user: &Address, shares: i128) {
+    let key = (USER_DEPOSITED_KEY, user.clone());
+    env.storage().persistent().set(&key, &shares);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, USER_LIFETIME_THRESHOLD, USER_BUMP_AMOUNT);
+}
+
+pub fn get_user_deposited(env: &Env, user: &Address) -> i128 {
+    let key = (USER_DEPOSITED_KEY, user.clone());
+    if let Some(shares) = env.storage().persistent().get::<_, i128>(&key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, USER_LIFETIME_THRESHOLD, USER_BUMP_AMOUNT);
+        shares
+    } else {
+        0
+    }
+}
+
 // Current exchange rate (updated on every operation until maturity)
 pub fn set_exchange_rate(env: &Env, rate: i128) {
     env.storage().instance().set(&EXCHANGE_RATE_KEY, &rate);
@@ -93,6 +199,38 @@ pub fn get_exchange_rate(env: &Env) -> i128 {
         .expect("Exchange rate not set")
 }
 
+// Ledger timestamp the vault rate was last read at before maturity
+pub fn set_rate_observed_at(env: &Env, timestamp: u64) {
+    env.storage().instance().set(&RATE_OBSERVED_AT_KEY, &timestamp);
+}
+
+pub fn get_rate_observed_at(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&RATE_OBSERVED_AT_KEY)
+        .expect("Rate observation time not set")
+}
+
+// Rate and ledger timestamp at deployment, the fixed reference yt_price measures growth from
+pub fn set_start(env: &Env, rate: i128, timestamp: u64) {
+    env.storage().instance().set(&START_RATE_KEY, &rate);
+    env.storage().instance().set(&START_TIME_KEY, &timestamp);
+}
+
+pub fn get_start(env: &Env) -> (i128, u64) {
+    let rate = env
+        .storage()
+        .instance()
+        .get(&START_RATE_KEY)
+        .expect("Start rate not set");
+    let timestamp = env
+        .storage()
+        .instance()
+        .get(&START_TIME_KEY)
+        .expect("Start time not set");
+    (rate, timestamp)
+}
+
 // Rate locked flag (set once when rate is locked at maturity)
 pub fn is_rate_locked(env: &Env) -> bool {
     env.storage()
@@ -115,4 +253,33 @@ pub fn is_initialized(env: &Env) -> bool {
 
 pub fn set_initialized(env: &Env) {
     env.storage().instance().set(&INITIALIZED_KEY, &true);
-}
\ No newline at end of file
+}
+
+// Paused flag (blocks new deposits, exits stay open)
+pub fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&PAUSED_KEY)
+        .unwrap_or(false)
+}
+
+pub fn set_paused(env: &Env, paused: bool) {
+    env.storage().instance().set(&PAUSED_KEY, &paused);
+}
+
+// Protocol fee on distributed yield, in basis points (defaults to no fee)
+pub fn set_fee(env: &Env, fee_bps: u32, fee_recipient: &Address) {
+    env.storage().instance().set(&FEE_BPS_KEY, &fee_bps);
+    env.storage().instance().set(&FEE_RECIPIENT_KEY, fee_recipient);
+}
+
+pub fn get_fee_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&FEE_BPS_KEY)
+        .unwrap_or(0)
+}
+
+pub fn get_fee_recipient(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&FEE_RECIPIENT_KEY)
+}
@@ -1,9 +1,11 @@
 #![cfg(test)]
-use crate::{YieldManager, VaultType};
+use crate::{storage, YieldManager, VaultType};
+use yield_manager_interface::Summary;
+use defindex_interface::AssetStrategySet;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
+    testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
     token::{StellarAssetClient, TokenClient},
-    Address, Env, IntoVal, String, Symbol,
+    contract, contractimpl, symbol_short, Address, Env, IntoVal, String, Symbol,
 };
 
 // Import contracts from the workspace
@@ -13,6 +15,11 @@ use yield_token::YieldToken;
 const VAULT_WASM: &[u8] = include_bytes!("../../../../wasms/vault.wasm");
 const HOLD_STRATEGY_WASM: &[u8] = include_bytes!("../../../../wasms/hold_strategy.wasm");
 
+// Stellar asset contracts always use 7 decimals
+const UNDERLYING_DECIMALS: u32 = 7;
+// PT/YT decimals matching the 1e6 rate scale over the underlying
+const TOKEN_DECIMALS: u32 = 13;
+
 struct YieldManagerTest {
     env: Env,
     admin: Address,
@@ -20,6 +27,7 @@ struct YieldManagerTest {
     user2: Address,
     underlying_asset_addr: Address,
     vault_addr: Address,
+    strategy_addr: Address,
     yield_manager: Address,
     pt: Address,
     yt: Address,
@@ -28,6 +36,12 @@ struct YieldManagerTest {
 
 impl YieldManagerTest {
     fn setup() -> Self {
+        Self::setup_with_rate_decimals(6)
+    }
+
+    // The manager quotes its rate at 10^rate_decimals, so PT/YT carry the asset's decimals plus those
+    fn setup_with_rate_decimals(rate_decimals: u32) -> Self {
+        let decimals = UNDERLYING_DECIMALS + rate_decimals;
         let env = Env::default();
         env.mock_all_auths();
 
@@ -40,18 +54,23 @@ impl YieldManagerTest {
         let underlying_asset_addr = env.register_stellar_asset_contract_v2(underlying_admin.clone());
         let underlying_asset_addr = underlying_asset_addr.address();
 
-        // Deploy hold strategy from WASM (no constructor parameters)
-        let strategy_id = env.register(HOLD_STRATEGY_WASM, ());
+        // Deploy vault from WASM with constructor parameters (asset, decimals_offset)
+        let vault_addr = env.register(VAULT_WASM, (&underlying_asset_addr, 0u32));
 
-        // Deploy vault from WASM with constructor parameters (asset, decimals_offset, strategy)
-        let vault_addr = env.register(VAULT_WASM, (&underlying_asset_addr, 0u32, &strategy_id));
+        // Deploy hold strategy from WASM with constructor parameters (vault, asset)
+        let strategy_addr = env.register(HOLD_STRATEGY_WASM, (&vault_addr, &underlying_asset_addr));
+        env.invoke_contract::<()>(
+            &vault_addr,
+            &Symbol::new(&env, "set_strategy"),
+            (&strategy_addr,).into_val(&env),
+        );
 
         // Set maturity to 1000 seconds from now
         let current_time = env.ledger().timestamp();
         let maturity = current_time + 1000;
 
         // Deploy yield manager
-        let yield_manager_id = env.register(YieldManager, (&admin, &vault_addr, VaultType::Vault4626, maturity));
+        let yield_manager_id = env.register(YieldManager, (&admin, &vault_addr, VaultType::Vault4626, maturity, 10i128.pow(rate_decimals)));
 
         // Deploy PT and YT tokens
         let pt_id = env.register(
@@ -60,6 +79,8 @@ impl YieldManagerTest {
                 &yield_manager_id,
                 String::from_str(&env, "Principal Token"),
                 String::from_str(&env, "PT"),
+                decimals,
+                0i128,
             ),
         );
 
@@ -67,6 +88,7 @@ impl YieldManagerTest {
             YieldToken,
             (
                 &yield_manager_id,
+                decimals,
                 String::from_str(&env, "Yield Token"),
                 String::from_str(&env, "YT"),
             ),
@@ -86,6 +108,7 @@ impl YieldManagerTest {
             user2,
             underlying_asset_addr,
             vault_addr,
+            strategy_addr,
             yield_manager: yield_manager_id,
             pt: pt_id,
             yt: yt_id,
@@ -132,6 +155,20 @@ impl YieldManagerTest {
             li.timestamp += seconds;
         });
     }
+
+    // The hold strategy earns nothing on its own, so yield is simulated by
+    // sending underlying straight to the strategy, which raises the vault's total assets
+    fn simulate_yield(&self, amount: i128) {
+        self.mint_underlying(&self.strategy_addr, amount);
+    }
+
+    fn get_exchange_rate(&self) -> i128 {
+        self.env.invoke_contract(
+            &self.yield_manager,
+            &Symbol::new(&self.env, "get_exchange_rate"),
+            ().into_val(&self.env),
+        )
+    }
 }
 
 #[test]
@@ -146,6 +183,20 @@ fn test_initialization() {
     );
     assert_eq!(vault_addr, test.vault_addr);
 
+    let vault_type: VaultType = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_vault_type"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(vault_type, VaultType::Vault4626);
+
+    let vault_info: (Address, VaultType) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_vault_info"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(vault_info, (test.vault_addr.clone(), VaultType::Vault4626));
+
     let maturity: u64 = test.env.invoke_contract(
         &test.yield_manager,
         &Symbol::new(&test.env, "get_maturity"),
@@ -154,20 +205,112 @@ fn test_initialization() {
     assert_eq!(maturity, test.maturity);
 }
 
+#[test]
+#[should_panic(expected = "Maturity must be in the future")]
+fn test_constructor_rejects_past_maturity() {
+    let test = YieldManagerTest::setup();
+    test.advance_time(5_000);
+    let now = test.env.ledger().timestamp();
+
+    test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, now - 1, 1_000_000i128),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Maturity must be in the future")]
+fn test_constructor_rejects_zero_maturity() {
+    let test = YieldManagerTest::setup();
+
+    test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, 0u64, 1_000_000i128),
+    );
+}
+
+#[test]
+fn test_constructor_accepts_future_maturity() {
+    let test = YieldManagerTest::setup();
+    test.advance_time(5_000);
+    let now = test.env.ledger().timestamp();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, now + 1, 1_000_000i128),
+    );
+    let maturity: u64 = test.env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&test.env, "get_maturity"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(maturity, now + 1);
+}
+
+#[test]
+fn test_get_underlying_asset() {
+    let test = YieldManagerTest::setup();
+
+    let asset: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_underlying_asset"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(asset, test.underlying_asset_addr);
+
+    // Cached after the first lookup
+    let cached = test
+        .env
+        .as_contract(&test.yield_manager, || storage::get_underlying_asset(&test.env));
+    assert_eq!(cached, Some(test.underlying_asset_addr.clone()));
+
+    let asset: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_underlying_asset"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(asset, test.underlying_asset_addr);
+}
+
+#[test]
+fn test_get_token_contracts_matches_individual_getters() {
+    let test = YieldManagerTest::setup();
+
+    let (pt, yt): (Address, Address) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_token_contracts"),
+        ().into_val(&test.env),
+    );
+    let pt_single: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_principal_token"),
+        ().into_val(&test.env),
+    );
+    let yt_single: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_yield_token"),
+        ().into_val(&test.env),
+    );
+
+    assert_eq!(pt, pt_single);
+    assert_eq!(yt, yt_single);
+    assert_eq!((pt, yt), (test.pt.clone(), test.yt.clone()));
+}
+
 #[test]
 fn test_deposit_mints_pt_and_yt() {
     let test = YieldManagerTest::setup();
 
     // User deposits underlying to vault
-    let deposit_amount = 1_000_0000i128; // 1000 units with 7 decimals
+    let deposit_amount = 10_000_000i128; // 1000 units with 7 decimals
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
 
     // User deposits vault shares to yield manager
-    test.env.invoke_contract::<()>(
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     // Check PT and YT balances
@@ -177,6 +320,8 @@ fn test_deposit_mints_pt_and_yt() {
     // Both should equal shares * exchange_rate
     // exchange_rate is 1_000_000 (1.0 scaled by 1e6) initially
     let expected_balance = shares * 1_000_000;
+    assert_eq!(pt_minted, expected_balance);
+    assert_eq!(yt_minted, expected_balance);
     assert_eq!(pt_balance, expected_balance);
     assert_eq!(yt_balance, expected_balance);
 
@@ -186,27 +331,179 @@ fn test_deposit_mints_pt_and_yt() {
 }
 
 #[test]
-fn test_exchange_rate_increases_over_time() {
+fn test_deposit_underlying_mints_pt_and_yt() {
     let test = YieldManagerTest::setup();
 
-    // Get initial exchange rate
-    let initial_rate: i128 = test.env.invoke_contract(
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+
+    // Only the user signs, the manager authorizes its own transfer into the vault
+    test.env.mock_auths(&[MockAuth {
+        address: &test.user1,
+        invoke: &MockAuthInvoke {
+            contract: &test.yield_manager,
+            fn_name: "deposit_underlying",
+            args: (&test.user1, deposit_amount, 0i128, 0i128).into_val(&test.env),
+            sub_invokes: &[MockAuthInvoke {
+                contract: &test.underlying_asset_addr,
+                fn_name: "transfer",
+                args: (&test.user1, &test.yield_manager, deposit_amount).into_val(&test.env),
+                sub_invokes: &[],
+            }],
+        },
+    }]);
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
         &test.yield_manager,
-        &Symbol::new(&test.env, "get_exchange_rate"),
-        ().into_val(&test.env),
+        &Symbol::new(&test.env, "deposit_underlying"),
+        (&test.user1, deposit_amount, 0i128, 0i128).into_val(&test.env),
     );
 
-    // Advance time by 100 seconds
-    test.advance_time(100);
+    // The shares are minted straight to the manager, the user never holds any
+    let shares = test.vault_balance(&test.yield_manager);
+    assert!(shares > 0);
+    assert_eq!(test.vault_balance(&test.user1), 0);
 
-    // Exchange rate should increase (vault accrues yield over time)
-    let new_rate: i128 = test.env.invoke_contract(
+    let expected_balance = shares * 1_000_000;
+    assert_eq!(pt_minted, expected_balance);
+    assert_eq!(yt_minted, expected_balance);
+    assert_eq!(test.get_pt_balance(&test.user1), expected_balance);
+    assert_eq!(test.get_yt_balance(&test.user1), expected_balance);
+
+    let underlying = TokenClient::new(&test.env, &test.underlying_asset_addr);
+    assert_eq!(underlying.balance(&test.user1), 0);
+    assert_eq!(underlying.balance(&test.yield_manager), 0);
+}
+
+#[test]
+fn test_deposit_underlying_min_out() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+
+    // 1:1 vault at a 1e6 rate, so the deposit mints deposit_amount * 1e6
+    let expected = deposit_amount * 1_000_000;
+
+    // Either bound above the minted amount reverts the whole deposit
+    for (min_pt_out, min_yt_out) in [(expected + 1, 0i128), (0i128, expected + 1)] {
+        let result = test.env.try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit_underlying"),
+            (&test.user1, deposit_amount, min_pt_out, min_yt_out).into_val(&test.env),
+        );
+        assert!(result.is_err());
+    }
+    let underlying = TokenClient::new(&test.env, &test.underlying_asset_addr);
+    assert_eq!(underlying.balance(&test.user1), deposit_amount);
+    assert_eq!(test.vault_balance(&test.yield_manager), 0);
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
         &test.yield_manager,
-        &Symbol::new(&test.env, "get_exchange_rate"),
-        ().into_val(&test.env),
+        &Symbol::new(&test.env, "deposit_underlying"),
+        (&test.user1, deposit_amount, expected, expected).into_val(&test.env),
+    );
+    assert_eq!(pt_minted, expected);
+    assert_eq!(yt_minted, expected);
+}
+
+#[test]
+fn test_deposit_min_out() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+
+    // The rate ticks up after the user quoted the deposit
+    let (quoted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "preview_deposit"),
+        (shares,).into_val(&test.env),
+    );
+    test.simulate_yield(deposit_amount / 10);
+
+    // Either bound above the minted amount reverts the deposit
+    for (min_pt_out, min_yt_out) in [(quoted * 2, 0i128), (0i128, quoted * 2)] {
+        let result = test.env.try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit"),
+            (&test.user1, shares, min_pt_out, min_yt_out).into_val(&test.env),
+        );
+        assert!(result.is_err());
+    }
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.user1), shares);
+
+    // A higher rate only mints more, so the quote is still satisfied
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, quoted, quoted).into_val(&test.env),
+    );
+    assert!(pt_minted > quoted);
+    assert_eq!(yt_minted, pt_minted);
+}
+
+#[test]
+#[should_panic(expected = "slippage")]
+fn test_deposit_below_min_out() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, shares * 1_000_000 + 1, 0i128).into_val(&test.env),
     );
+}
+
+#[test]
+fn test_exchange_rate_increases_over_time() {
+    let test = YieldManagerTest::setup();
+
+    // Seed the vault so it has shares outstanding
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    test.vault_deposit(&test.user1, deposit_amount);
+
+    // Get initial exchange rate
+    let initial_rate = test.get_exchange_rate();
+
+    // Advance time by 100 seconds while the vault earns 10%
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+
+    // Exchange rate should increase (vault accrues yield over time)
+    let new_rate = test.get_exchange_rate();
 
     assert!(new_rate > initial_rate);
+    assert_eq!(new_rate, 1_100_000);
+}
+
+#[test]
+fn test_exchange_rate_on_empty_vault() {
+    let test = YieldManagerTest::setup();
+
+    // Nothing has been deposited into the vault yet, the rate still reads as 1.0
+    assert_eq!(test.get_exchange_rate(), 1_000_000);
+
+    // The first deposit into the vault and manager mints consistently with that rate
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    assert_eq!(shares, deposit_amount);
+
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(test.get_pt_balance(&test.user1), shares * 1_000_000);
+    assert_eq!(test.get_exchange_rate(), 1_000_000);
 }
 
 #[test]
@@ -214,13 +511,13 @@ fn test_yt_accrues_yield_over_time() {
     let test = YieldManagerTest::setup();
 
     // User deposits
-    let deposit_amount = 1_000_0000i128;
+    let deposit_amount = 10_000_000i128;
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     // Check initial accrued yield (should be 0)
@@ -233,6 +530,7 @@ fn test_yt_accrues_yield_over_time() {
 
     // Advance time to accrue yield
     test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
 
     // Trigger yield accrual by calling claim_yield
     let claimed: i128 = test.env.invoke_contract(
@@ -253,8 +551,13 @@ fn test_yt_accrues_yield_over_time() {
 fn test_exchange_rate_locks_at_maturity() {
     let test = YieldManagerTest::setup();
 
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    test.vault_deposit(&test.user1, deposit_amount);
+
     // Get exchange rate before maturity
     test.advance_time(500); // Halfway to maturity
+    test.simulate_yield(deposit_amount / 10);
     let rate_before_maturity: i128 = test.env.invoke_contract(
         &test.yield_manager,
         &Symbol::new(&test.env, "get_exchange_rate"),
@@ -263,6 +566,7 @@ fn test_exchange_rate_locks_at_maturity() {
 
     // Advance past maturity
     test.advance_time(600); // Now past maturity (500 + 600 > 1000)
+    test.simulate_yield(deposit_amount / 10);
 
     // Get exchange rate at maturity (should be locked)
     let rate_at_maturity: i128 = test.env.invoke_contract(
@@ -271,11 +575,14 @@ fn test_exchange_rate_locks_at_maturity() {
         ().into_val(&test.env),
     );
 
-    // Rate should be higher than before maturity
-    assert!(rate_at_maturity > rate_before_maturity);
+    // The 10% earned between the last observation and the first call after maturity
+    // is prorated up to maturity, the rest of it isn't captured
+    assert_eq!(rate_before_maturity, 1_100_000);
+    assert_eq!(rate_at_maturity, 1_100_000 + 100_000 * 500 / 600);
 
     // Advance time further
     test.advance_time(1000);
+    test.simulate_yield(deposit_amount / 10);
 
     // Rate should still be the same (locked at maturity)
     let rate_after_maturity: i128 = test.env.invoke_contract(
@@ -286,6 +593,136 @@ fn test_exchange_rate_locks_at_maturity() {
     assert_eq!(rate_after_maturity, rate_at_maturity);
 }
 
+#[test]
+fn test_lock_rate_same_in_either_order() {
+    let lock_rate = |test: &YieldManagerTest| -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "lock_rate"),
+            ().into_val(&test.env),
+        )
+    };
+    let is_rate_locked = |test: &YieldManagerTest| -> bool {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "is_rate_locked"),
+            ().into_val(&test.env),
+        )
+    };
+
+    // Two identical managers see 10% growth before maturity and 10% more after it
+    let deposit_amount = 10_000_000i128;
+    let lock_first = YieldManagerTest::setup();
+    let update_first = YieldManagerTest::setup();
+    for test in [&lock_first, &update_first] {
+        test.mint_underlying(&test.user1, deposit_amount);
+        test.vault_deposit(&test.user1, deposit_amount);
+        test.advance_time(500);
+        test.simulate_yield(deposit_amount / 10);
+        test.get_exchange_rate();
+        test.advance_time(501);
+        test.simulate_yield(deposit_amount / 10);
+        assert!(!is_rate_locked(test));
+    }
+
+    // lock_rate then a rate update, against a rate update then lock_rate
+    let locked = lock_rate(&lock_first);
+    assert_eq!(lock_first.get_exchange_rate(), locked);
+    assert_eq!(update_first.get_exchange_rate(), locked);
+    assert_eq!(lock_rate(&update_first), locked);
+
+    // Both locked the growth since the last observation prorated up to maturity, and stay locked
+    assert_eq!(locked, 1_100_000 + 100_000 * 500 / 501);
+    for test in [&lock_first, &update_first] {
+        assert!(is_rate_locked(test));
+        test.simulate_yield(deposit_amount / 10);
+        assert_eq!(test.get_exchange_rate(), locked);
+        assert_eq!(lock_rate(test), locked);
+    }
+}
+
+#[test]
+fn test_lock_rate_same_early_or_late() {
+    let lock_rate = |test: &YieldManagerTest| -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "lock_rate"),
+            ().into_val(&test.env),
+        )
+    };
+
+    // Two identical managers last see the rate at 1.1 before maturity, the vault
+    // keeps earning 10% every 500 seconds
+    let deposit_amount = 10_000_000i128;
+    let early = YieldManagerTest::setup();
+    let late = YieldManagerTest::setup();
+    for test in [&early, &late] {
+        test.mint_underlying(&test.user1, deposit_amount);
+        test.vault_deposit(&test.user1, deposit_amount);
+        test.advance_time(500);
+        test.simulate_yield(deposit_amount / 10);
+        assert_eq!(test.get_exchange_rate(), 1_100_000);
+        test.advance_time(500);
+        test.simulate_yield(deposit_amount / 10);
+    }
+
+    // One locks right at maturity, the other only after the vault earned 20% more
+    let locked_early = lock_rate(&early);
+
+    late.advance_time(1000);
+    late.simulate_yield(2 * deposit_amount / 10);
+    let peeked: i128 = late.env.invoke_contract(
+        &late.yield_manager,
+        &Symbol::new(&late.env, "peek_exchange_rate"),
+        ().into_val(&late.env),
+    );
+    let locked_late = lock_rate(&late);
+
+    assert_eq!(locked_early, 1_200_000);
+    assert_eq!(locked_late, locked_early);
+    assert_eq!(peeked, locked_late);
+}
+
+#[test]
+fn test_lock_rate_after_long_idle_gap() {
+    let test = YieldManagerTest::setup();
+
+    // Nobody touches the manager after deployment while the vault earns 10% a maturity period
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    test.vault_deposit(&test.user1, deposit_amount);
+    test.advance_time(10_000);
+    test.simulate_yield(deposit_amount);
+
+    // The lock is nine periods late, the rate at deployment would be stale by 10%. The
+    // growth since then is prorated instead, landing on the rate at maturity
+    let locked: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "lock_rate"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(locked, 1_100_000);
+    let vault_rate: i128 = test.env.invoke_contract(
+        &test.vault_addr,
+        &Symbol::new(&test.env, "convert_to_assets"),
+        (1_000_000i128,).into_val(&test.env),
+    );
+    assert_eq!(vault_rate, 2_000_000);
+}
+
+#[test]
+#[should_panic(expected = "Maturity not reached")]
+fn test_lock_rate_before_maturity() {
+    let test = YieldManagerTest::setup();
+
+    test.advance_time(999);
+    test.env.invoke_contract::<i128>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "lock_rate"),
+        ().into_val(&test.env),
+    );
+}
+
 // Note: This test is disabled because the real vault (with hold strategy) doesn't have
 // a way to simulate decreasing exchange rates like the mock vault did.
 // The high water mark feature can be tested with a different vault implementation.
@@ -314,28 +751,68 @@ fn test_exchange_rate_high_water_mark() {
     assert!(higher_rate > initial_rate);
 }
 
+#[test]
+fn test_is_matured_flips_at_maturity() {
+    let test = YieldManagerTest::setup();
+    let is_matured = || -> bool {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "is_matured"),
+            ().into_val(&test.env),
+        )
+    };
+
+    assert!(!is_matured());
+
+    test.env.ledger().with_mut(|li| li.timestamp = test.maturity - 1);
+    assert!(!is_matured());
+
+    test.env.ledger().with_mut(|li| li.timestamp = test.maturity);
+    assert!(is_matured());
+
+    test.env.ledger().with_mut(|li| li.timestamp = test.maturity + 1);
+    assert!(is_matured());
+}
+
+#[test]
+#[should_panic(expected = "Maturity reached")]
+fn test_cannot_deposit_at_maturity() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+
+    test.env.ledger().with_mut(|li| li.timestamp = test.maturity);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+}
+
 #[test]
 #[should_panic(expected = "Maturity not reached")]
 fn test_cannot_redeem_principal_before_maturity() {
     let test = YieldManagerTest::setup();
 
     // User deposits
-    let deposit_amount = 1_000_0000i128;
+    let deposit_amount = 10_000_000i128;
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     let pt_balance = test.get_pt_balance(&test.user1);
 
     // Try to redeem PT before maturity (should panic)
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<i128>(
         &test.yield_manager,
         &Symbol::new(&test.env, "redeem_principal"),
-        (&test.user1, pt_balance).into_val(&test.env),
+        (&test.user1, pt_balance, 0i128).into_val(&test.env),
     );
 }
 
@@ -344,13 +821,13 @@ fn test_redeem_principal_after_maturity() {
     let test = YieldManagerTest::setup();
 
     // User deposits
-    let deposit_amount = 1_000_0000i128;
+    let deposit_amount = 10_000_000i128;
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     let pt_balance = test.get_pt_balance(&test.user1);
@@ -359,11 +836,12 @@ fn test_redeem_principal_after_maturity() {
     test.advance_time(1100);
 
     // Redeem PT for vault shares
-    test.env.invoke_contract::<()>(
+    let shares_returned: i128 = test.env.invoke_contract(
         &test.yield_manager,
         &Symbol::new(&test.env, "redeem_principal"),
-        (&test.user1, pt_balance).into_val(&test.env),
+        (&test.user1, pt_balance, 0i128).into_val(&test.env),
     );
+    assert_eq!(shares_returned, shares);
 
     // Check PT was burned
     let pt_balance_after = test.get_pt_balance(&test.user1);
@@ -371,67 +849,297 @@ fn test_redeem_principal_after_maturity() {
 
     // User should have received vault shares back
     let user_vault_balance = test.vault_balance(&test.user1);
-    assert!(user_vault_balance > 0);
+    assert_eq!(user_vault_balance, shares_returned);
 }
 
 #[test]
-fn test_multiple_users_deposit() {
+fn test_redeem_principal_haircut_when_underwater() {
     let test = YieldManagerTest::setup();
 
-    // User1 deposits
-    let deposit1 = 1_000_0000i128;
-    test.mint_underlying(&test.user1, deposit1);
-    let shares1 = test.vault_deposit(&test.user1, deposit1);
-    test.env.invoke_contract::<()>(
-        &test.yield_manager,
-        &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares1).into_val(&test.env),
-    );
+    let deposit_amount = 10_000_000i128;
+    let mut shares = 0;
+    for user in [&test.user1, &test.user2] {
+        test.mint_underlying(user, deposit_amount);
+        shares = test.vault_deposit(user, deposit_amount);
+        test.env.invoke_contract::<(i128, i128)>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit"),
+            (user, shares, 0i128, 0i128).into_val(&test.env),
+        );
+    }
 
-    // User2 deposits
-    let deposit2 = 2_000_0000i128;
-    test.mint_underlying(&test.user2, deposit2);
-    let shares2 = test.vault_deposit(&test.user2, deposit2);
-    test.env.invoke_contract::<()>(
-        &test.yield_manager,
-        &Symbol::new(&test.env, "deposit"),
-        (&test.user2, shares2).into_val(&test.env),
-    );
+    // A fifth of the shares backing the PT leave the manager, e.g. through an exploit
+    let vault = TokenClient::new(&test.env, &test.vault_addr);
+    let sink = Address::generate(&test.env);
+    vault.transfer(&test.yield_manager, &sink, &(2 * shares / 5));
 
-    // Check balances
-    let pt1 = test.get_pt_balance(&test.user1);
-    let pt2 = test.get_pt_balance(&test.user2);
+    test.advance_time(1100);
 
-    // User2 should have roughly 2x the PT of User1
-    assert!(pt2 > pt1);
-    assert!(pt2 >= pt1 * 2 - 100); // Allow some rounding
+    // Both holders take the same 20% haircut whatever the order they redeem in
+    // and the preview quotes the haircut amount actually paid
+    for user in [&test.user1, &test.user2] {
+        let pt_balance = test.get_pt_balance(user);
+        let previewed: i128 = test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "preview_redeem"),
+            (pt_balance,).into_val(&test.env),
+        );
+        let shares_out: i128 = test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "redeem_principal"),
+            (user, pt_balance, 0i128).into_val(&test.env),
+        );
+        assert_eq!(shares_out, shares * 4 / 5);
+        assert_eq!(previewed, shares_out);
+        assert_eq!(test.get_pt_balance(user), 0);
+    }
+    assert_eq!(test.vault_balance(&test.yield_manager), 0);
 }
 
 #[test]
-fn test_yield_distribution_proportional() {
+fn test_redeem_combined_haircut_when_underwater() {
     let test = YieldManagerTest::setup();
 
-    // Both users deposit equal amounts
-    let deposit_amount = 1_000_0000i128;
-
-    test.mint_underlying(&test.user1, deposit_amount);
-    let shares1 = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
-        &test.yield_manager,
-        &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares1).into_val(&test.env),
-    );
+    let deposit_amount = 10_000_000i128;
+    let mut shares = 0;
+    for user in [&test.user1, &test.user2] {
+        test.mint_underlying(user, deposit_amount);
+        shares = test.vault_deposit(user, deposit_amount);
+        test.env.invoke_contract::<(i128, i128)>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit"),
+            (user, shares, 0i128, 0i128).into_val(&test.env),
+        );
+    }
 
-    test.mint_underlying(&test.user2, deposit_amount);
+    // A fifth of the shares backing the PT leave the manager before maturity
+    let vault = TokenClient::new(&test.env, &test.vault_addr);
+    let sink = Address::generate(&test.env);
+    vault.transfer(&test.yield_manager, &sink, &(2 * shares / 5));
+
+    // Exiting with PT and YT takes the same haircut as a PT redemption, so the
+    // first holder out can't take the second one's shares
+    for user in [&test.user1, &test.user2] {
+        let pt_balance = test.get_pt_balance(user);
+        test.env.invoke_contract::<()>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "redeem_combined"),
+            (user, pt_balance).into_val(&test.env),
+        );
+        assert_eq!(test.vault_balance(user), shares * 4 / 5);
+        assert_eq!(test.get_pt_balance(user), 0);
+        assert_eq!(test.get_yt_balance(user), 0);
+    }
+    assert_eq!(test.vault_balance(&test.yield_manager), 0);
+}
+
+#[test]
+fn test_redeem_principal_haircut_leaves_unclaimed_yield() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    let mut shares = 0;
+    for user in [&test.user1, &test.user2] {
+        test.mint_underlying(user, deposit_amount);
+        shares = test.vault_deposit(user, deposit_amount);
+        test.env.invoke_contract::<(i128, i128)>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit"),
+            (user, shares, 0i128, 0i128).into_val(&test.env),
+        );
+    }
+
+    // 10% yield accrues to the YT, nobody claims it
+    test.simulate_yield(2 * deposit_amount / 10);
+    assert_eq!(test.get_exchange_rate(), 1_100_000);
+    let yt_owed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claimable_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(yt_owed, shares * 100_000 / 1_100_000);
+
+    // A fifth of the manager's shares leave it, the PT alone is now underwater
+    let vault = TokenClient::new(&test.env, &test.vault_addr);
+    let sink = Address::generate(&test.env);
+    vault.transfer(&test.yield_manager, &sink, &(2 * shares / 5));
+
+    test.advance_time(1100);
+
+    // user1 claims its yield before redeeming, user2 after: the PT haircut is the same
+    // either way and both YT holders are paid in full
+    let claim = |user: &Address| -> i128 {
+        test.env.invoke_contract(
+            &test.yt,
+            &Symbol::new(&test.env, "claim_yield"),
+            (user,).into_val(&test.env),
+        )
+    };
+    let redeem = |user: &Address| -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "redeem_all"),
+            (user,).into_val(&test.env),
+        )
+    };
+    assert_eq!(claim(&test.user1), yt_owed);
+    let shares_out1 = redeem(&test.user1);
+    let shares_out2 = redeem(&test.user2);
+    assert_eq!(claim(&test.user2), yt_owed);
+
+    // The backing is what's left once the yield is set aside, split evenly
+    let backing = 2 * shares - 2 * shares / 5 - 2 * yt_owed;
+    assert!((shares_out1 - backing / 2).abs() <= 1);
+    assert!((shares_out2 - backing / 2).abs() <= 1);
+    assert!(test.vault_balance(&test.yield_manager) <= 2);
+}
+
+#[test]
+fn test_redeem_principal_to_underlying() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    let pt_balance = test.get_pt_balance(&test.user1);
+    test.advance_time(1100);
+
+    // Less PT than one share's worth is left untouched
+    let dust: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal_to_underlying"),
+        (&test.user1, 999_999i128).into_val(&test.env),
+    );
+    assert_eq!(dust, 0);
+    assert_eq!(test.get_pt_balance(&test.user1), pt_balance);
+
+    let assets: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal_to_underlying"),
+        (&test.user1, pt_balance).into_val(&test.env),
+    );
+    assert_eq!(assets, deposit_amount);
+
+    // PT burned and the underlying paid out directly, no shares left anywhere
+    let underlying = TokenClient::new(&test.env, &test.underlying_asset_addr);
+    assert_eq!(underlying.balance(&test.user1), assets);
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.yield_manager), 0);
+}
+
+#[test]
+fn test_redeem_all_after_maturity() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(1100);
+
+    // No balance lookup needed, the whole PT position is redeemed
+    let shares_returned: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_all"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(shares_returned, shares);
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.user1), shares_returned);
+}
+
+#[test]
+#[should_panic(expected = "Maturity not reached")]
+fn test_redeem_all_before_maturity() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.env.invoke_contract::<i128>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_all"),
+        (&test.user1,).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_multiple_users_deposit() {
+    let test = YieldManagerTest::setup();
+
+    // User1 deposits
+    let deposit1 = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit1);
+    let shares1 = test.vault_deposit(&test.user1, deposit1);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares1, 0i128, 0i128).into_val(&test.env),
+    );
+
+    // User2 deposits
+    let deposit2 = 20_000_000i128;
+    test.mint_underlying(&test.user2, deposit2);
+    let shares2 = test.vault_deposit(&test.user2, deposit2);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user2, shares2, 0i128, 0i128).into_val(&test.env),
+    );
+
+    // Check balances
+    let pt1 = test.get_pt_balance(&test.user1);
+    let pt2 = test.get_pt_balance(&test.user2);
+
+    // User2 should have roughly 2x the PT of User1
+    assert!(pt2 > pt1);
+    assert!(pt2 >= pt1 * 2 - 100); // Allow some rounding
+}
+
+#[test]
+fn test_yield_distribution_proportional() {
+    let test = YieldManagerTest::setup();
+
+    // Both users deposit equal amounts
+    let deposit_amount = 10_000_000i128;
+
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares1 = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares1, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.mint_underlying(&test.user2, deposit_amount);
     let shares2 = test.vault_deposit(&test.user2, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user2, shares2).into_val(&test.env),
+        (&test.user2, shares2, 0i128, 0i128).into_val(&test.env),
     );
 
     // Advance time to accrue yield
     test.advance_time(200);
+    test.simulate_yield(deposit_amount / 5);
 
     // Both claim yield
     let claimed1: i128 = test.env.invoke_contract(
@@ -455,18 +1163,277 @@ fn test_yield_distribution_proportional() {
     assert!(diff < claimed1 / 100);
 }
 
+#[test]
+fn test_total_principal_shares() {
+    let test = YieldManagerTest::setup();
+    let total_principal_shares = || -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "total_principal_shares"),
+            ().into_val(&test.env),
+        )
+    };
+    assert_eq!(total_principal_shares(), 0);
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(total_principal_shares(), shares);
+
+    // Yield paid out of the manager's balance is not principal
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+    let claimed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claim_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!(claimed > 0);
+    assert_eq!(test.vault_balance(&test.yield_manager), shares - claimed);
+    assert_eq!(total_principal_shares(), shares);
+
+    test.advance_time(1000);
+    let pt_balance = test.get_pt_balance(&test.user1);
+    let redeemed: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_balance / 2, 0i128).into_val(&test.env),
+    );
+    assert!(redeemed > 0);
+    assert_eq!(total_principal_shares(), shares - redeemed);
+}
+
+#[test]
+fn test_vault_shares_reconcile_across_lifecycle() {
+    let test = YieldManagerTest::setup();
+    let user3 = Address::generate(&test.env);
+    let users = [test.user1.clone(), test.user2.clone(), user3.clone()];
+
+    let deposit = |user: &Address, assets: i128| {
+        test.mint_underlying(user, assets);
+        let shares = test.vault_deposit(user, assets);
+        test.env.invoke_contract::<(i128, i128)>(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "deposit"),
+            (user, shares, 0i128, 0i128).into_val(&test.env),
+        );
+    };
+    let claim = |user: &Address| -> i128 {
+        test.env.invoke_contract(
+            &test.yt,
+            &Symbol::new(&test.env, "claim_yield"),
+            (user,).into_val(&test.env),
+        )
+    };
+    // Shares owed to PT holders at the current rate plus yield owed to YT holders
+    let owed = || -> i128 {
+        test.get_exchange_rate();
+        let mut owed = 0i128;
+        for user in users.iter() {
+            let pt_balance = test.get_pt_balance(user);
+            owed += test.env.invoke_contract::<i128>(
+                &test.yield_manager,
+                &Symbol::new(&test.env, "preview_redeem"),
+                (pt_balance,).into_val(&test.env),
+            );
+            owed += test.env.invoke_contract::<i128>(
+                &test.yt,
+                &Symbol::new(&test.env, "claimable_yield"),
+                (user,).into_val(&test.env),
+            );
+        }
+        owed
+    };
+    // Every term rounds down, so the manager may only hold a few units of dust on top
+    let assert_reconciles = || {
+        let balance = test.vault_balance(&test.yield_manager);
+        let owed = owed();
+        assert!(balance >= owed, "manager short: {} < {}", balance, owed);
+        assert!(balance - owed <= 10, "unaccounted shares: {} > {}", balance, owed);
+    };
+
+    deposit(&test.user1, 10_000_000);
+    assert_reconciles();
+
+    // user2 enters at a higher rate, so its PT and shares differ
+    test.advance_time(100);
+    test.simulate_yield(1_000_000);
+    deposit(&test.user2, 7_000_000);
+    assert_reconciles();
+
+    // user1 claims mid-term and hands part of its YT to user3
+    test.advance_time(100);
+    test.simulate_yield(2_000_000);
+    assert!(claim(&test.user1) > 0);
+    let yt_balance = test.get_yt_balance(&test.user1);
+    test.env.invoke_contract::<()>(
+        &test.yt,
+        &Symbol::new(&test.env, "transfer"),
+        (&test.user1, &user3, yt_balance / 3).into_val(&test.env),
+    );
+    assert_reconciles();
+
+    // More yield, then the rate locks at maturity
+    test.advance_time(300);
+    test.simulate_yield(3_000_000);
+    assert_reconciles();
+    test.advance_time(600);
+    test.simulate_yield(5_000_000);
+    assert_reconciles();
+
+    // Everyone redeems PT and claims what's left, leaving only dust behind
+    for user in users.iter() {
+        if test.get_pt_balance(user) > 0 {
+            test.env.invoke_contract::<i128>(
+                &test.yield_manager,
+                &Symbol::new(&test.env, "redeem_all"),
+                (user,).into_val(&test.env),
+            );
+        }
+        claim(user);
+        assert_reconciles();
+    }
+    assert_eq!(owed(), 0);
+    assert!(test.vault_balance(&test.yield_manager) <= 10);
+}
+
+#[test]
+#[should_panic(expected = "per-user cap exceeded")]
+fn test_deposit_over_per_user_cap() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_per_user_cap"),
+        (shares - 1,).into_val(&test.env),
+    );
+
+    // Up to the cap is fine, one share past it reverts
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares - 1, 0i128, 0i128).into_val(&test.env),
+    );
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, 1i128, 0i128, 0i128).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_redeem_frees_per_user_cap() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_per_user_cap"),
+        (shares,).into_val(&test.env),
+    );
+    let user_deposited = || -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "get_user_deposited"),
+            (&test.user1,).into_val(&test.env),
+        )
+    };
+
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(user_deposited(), shares);
+
+    // Exiting with PT + YT before maturity frees the cap for a fresh deposit
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_combined"),
+        (&test.user1, pt_minted).into_val(&test.env),
+    );
+    assert_eq!(user_deposited(), 0);
+
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(user_deposited(), shares);
+}
+
+#[test]
+fn test_full_exit_frees_per_user_cap_after_rate_rise() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_per_user_cap"),
+        (shares,).into_val(&test.env),
+    );
+    let user_deposited = || -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "get_user_deposited"),
+            (&test.user1,).into_val(&test.env),
+        )
+    };
+
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    // At a 10% higher rate the exit pays out fewer shares than were deposited
+    test.simulate_yield(deposit_amount / 10);
+    assert_eq!(test.get_exchange_rate(), 1_100_000);
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_combined"),
+        (&test.user1, pt_minted).into_val(&test.env),
+    );
+    // Only PT dust below one share's worth is left behind
+    assert!(test.vault_balance(&test.user1) < shares);
+    assert!(test.get_pt_balance(&test.user1) < 1_100_000);
+    assert_eq!(user_deposited(), 0);
+
+    // The whole cap is available again
+    test.mint_underlying(&test.user1, 2 * deposit_amount);
+    test.vault_deposit(&test.user1, 2 * deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(user_deposited(), shares);
+}
+
 #[test]
 fn test_pt_transferable() {
     let test = YieldManagerTest::setup();
 
     // User1 deposits
-    let deposit_amount = 1_000_0000i128;
+    let deposit_amount = 10_000_000i128;
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     let pt_balance = test.get_pt_balance(&test.user1);
@@ -492,13 +1459,13 @@ fn test_yt_transferable() {
     let test = YieldManagerTest::setup();
 
     // User1 deposits
-    let deposit_amount = 1_000_0000i128;
+    let deposit_amount = 10_000_000i128;
     test.mint_underlying(&test.user1, deposit_amount);
     let shares = test.vault_deposit(&test.user1, deposit_amount);
-    test.env.invoke_contract::<()>(
+    test.env.invoke_contract::<(i128, i128)>(
         &test.yield_manager,
         &Symbol::new(&test.env, "deposit"),
-        (&test.user1, shares).into_val(&test.env),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
     );
 
     let yt_balance = test.get_yt_balance(&test.user1);
@@ -518,3 +1485,1766 @@ fn test_yt_transferable() {
     assert_eq!(yt1_after, yt_balance - transfer_amount);
     assert_eq!(yt2_after, transfer_amount);
 }
+
+#[test]
+fn test_preview_deposit_matches_deposit() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+
+    // Vault earns yield that the manager has not stored yet
+    test.simulate_yield(deposit_amount / 10);
+
+    let (pt_preview, yt_preview): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "preview_deposit"),
+        (shares,).into_val(&test.env),
+    );
+
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    assert_eq!((pt_preview, yt_preview), (pt_minted, yt_minted));
+    assert_eq!(pt_preview, test.get_pt_balance(&test.user1));
+    assert_eq!(yt_preview, test.get_yt_balance(&test.user1));
+    assert_eq!(pt_preview, shares * 1_100_000);
+}
+
+#[test]
+fn test_preview_redeem_matches_redeem() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(1100);
+
+    // Redeem an amount that doesn't divide evenly by the rate
+    let pt_amount = test.get_pt_balance(&test.user1) / 3;
+    let shares_preview: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "preview_redeem"),
+        (pt_amount,).into_val(&test.env),
+    );
+
+    let shares_returned: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_amount, 0i128).into_val(&test.env),
+    );
+
+    assert_eq!(shares_preview, shares_returned);
+    assert_eq!(shares_preview, test.vault_balance(&test.user1));
+    assert_eq!(shares_preview, pt_amount / 1_000_000);
+}
+
+#[test]
+fn test_redeem_principal_min_shares_out() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    let (pt_minted, _) = test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    test.advance_time(1100);
+
+    // Asking for more shares than the locked rate gives reverts
+    let result = test.env.try_invoke_contract::<i128, soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_minted, shares + 1).into_val(&test.env),
+    );
+    assert!(result.is_err());
+    assert_eq!(test.get_pt_balance(&test.user1), pt_minted);
+
+    // Exactly the expected output passes the guard
+    let redeemed: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_minted, shares).into_val(&test.env),
+    );
+    assert_eq!(redeemed, shares);
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+}
+
+#[test]
+fn test_redeem_principal_keeps_dust_pt() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(1100);
+
+    // Not an exact multiple of the 1e6 exchange rate
+    let exchange_rate = 1_000_000i128;
+    let pt_balance = test.get_pt_balance(&test.user1);
+    let pt_amount = 5 * exchange_rate + 123_456;
+
+    let shares_returned: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_amount, 0i128).into_val(&test.env),
+    );
+
+    // Only the PT backing the 5 returned shares is burned
+    let pt_consumed = 5 * exchange_rate;
+    assert_eq!(shares_returned, 5);
+    assert_eq!(test.vault_balance(&test.user1), 5);
+    assert_eq!(test.get_pt_balance(&test.user1), pt_balance - pt_consumed);
+    assert_eq!(pt_amount - pt_consumed, 123_456);
+}
+
+#[test]
+#[should_panic(expected = "mint amount overflow")]
+fn test_deposit_mint_amount_overflow() {
+    let test = YieldManagerTest::setup();
+
+    // shares * 1e6 no longer fits in an i128
+    let deposit_amount = i128::MAX / 1_000_000 + 1;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_peek_exchange_rate_matches_persisted_rate() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    test.vault_deposit(&test.user1, deposit_amount);
+
+    test.simulate_yield(deposit_amount / 10);
+
+    let peeked: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "peek_exchange_rate"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(peeked, 1_100_000);
+    assert_eq!(test.get_exchange_rate(), peeked);
+
+    // Once locked, peeking ignores further vault yield
+    test.advance_time(1000);
+    test.get_exchange_rate();
+    test.simulate_yield(deposit_amount / 10);
+
+    let peeked_after_lock: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "peek_exchange_rate"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(peeked_after_lock, peeked);
+    assert_eq!(test.get_exchange_rate(), peeked);
+}
+
+#[test]
+fn test_peek_exchange_rate_does_not_lock() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    test.vault_deposit(&test.user1, deposit_amount);
+    test.simulate_yield(deposit_amount / 10);
+
+    let peek = |test: &YieldManagerTest| -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "peek_exchange_rate"),
+            ().into_val(&test.env),
+        )
+    };
+    let is_locked = |test: &YieldManagerTest| {
+        test.env.as_contract(&test.yield_manager, || storage::is_rate_locked(&test.env))
+    };
+
+    // Before maturity the peek reports the high-water mark without storing it
+    assert_eq!(peek(&test), 1_100_000);
+    test.env.as_contract(&test.yield_manager, || {
+        assert_eq!(storage::get_exchange_rate(&test.env), 1_000_000);
+    });
+    assert_eq!(test.get_exchange_rate(), peek(&test));
+    assert!(!is_locked(&test));
+
+    // Past maturity the peek still doesn't lock, only the mutating getter does
+    test.advance_time(1000);
+    test.simulate_yield(deposit_amount / 10);
+    let peeked = peek(&test);
+    assert!(!is_locked(&test));
+    assert_eq!(test.get_exchange_rate(), peeked);
+    assert!(is_locked(&test));
+}
+
+#[test]
+fn test_redeem_combined_before_maturity() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    let pt_balance = test.get_pt_balance(&test.user1);
+    let yt_balance = test.get_yt_balance(&test.user1);
+
+    // Rate moves to 1.1e6 before the user recombines half their position
+    test.simulate_yield(deposit_amount / 10);
+    let amount = pt_balance / 2;
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_combined"),
+        (&test.user1, amount).into_val(&test.env),
+    );
+
+    let shares_returned = amount / 1_100_000;
+    let amount_consumed = shares_returned * 1_100_000;
+    assert_eq!(test.vault_balance(&test.user1), shares_returned);
+    assert_eq!(test.get_pt_balance(&test.user1), pt_balance - amount_consumed);
+    assert_eq!(test.get_yt_balance(&test.user1), yt_balance - amount_consumed);
+
+    // Yield earned on the burned YT was accrued before the burn
+    let accrued: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "accrued_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!(accrued > 0);
+}
+
+#[test]
+#[should_panic(expected = "insufficient balance")]
+fn test_redeem_combined_insufficient_pt() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    // User1 keeps all YT but sends half the PT away
+    let pt_balance = test.get_pt_balance(&test.user1);
+    test.env.invoke_contract::<()>(
+        &test.pt,
+        &Symbol::new(&test.env, "transfer"),
+        (&test.user1, &test.user2, pt_balance / 2).into_val(&test.env),
+    );
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_combined"),
+        (&test.user1, pt_balance).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Insufficient balance")]
+fn test_redeem_combined_insufficient_yt() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    // User1 keeps all PT but sends half the YT away
+    let yt_balance = test.get_yt_balance(&test.user1);
+    test.env.invoke_contract::<()>(
+        &test.yt,
+        &Symbol::new(&test.env, "transfer"),
+        (&test.user1, &test.user2, yt_balance / 2).into_val(&test.env),
+    );
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_combined"),
+        (&test.user1, yt_balance).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_pause_blocks_deposits_but_not_redemptions() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares / 2, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pause"),
+        ().into_val(&test.env),
+    );
+    let paused: bool = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "is_paused"),
+        ().into_val(&test.env),
+    );
+    assert!(paused);
+
+    // New deposits revert while paused
+    let result = test.env.try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares / 2, 0i128, 0i128).into_val(&test.env),
+    );
+    assert!(result.is_err());
+
+    // Redemptions still go through
+    test.advance_time(1100);
+    let pt_balance = test.get_pt_balance(&test.user1);
+    test.env.invoke_contract::<i128>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_balance, 0i128).into_val(&test.env),
+    );
+    assert_eq!(test.get_pt_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.user1), shares);
+}
+
+#[test]
+#[should_panic(expected = "contract paused")]
+fn test_deposit_fails_while_paused() {
+    let test = YieldManagerTest::setup();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pause"),
+        ().into_val(&test.env),
+    );
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_unpause_restores_deposits() {
+    let test = YieldManagerTest::setup();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pause"),
+        ().into_val(&test.env),
+    );
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "unpause"),
+        ().into_val(&test.env),
+    );
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(test.get_pt_balance(&test.user1), shares * 1_000_000);
+}
+
+#[test]
+fn test_admin_pauses_principal_token_through_manager() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pause_principal_token"),
+        ().into_val(&test.env),
+    );
+    let pt_client = TokenClient::new(&test.env, &test.pt);
+    let paused: bool = test.env.invoke_contract(
+        &test.pt,
+        &Symbol::new(&test.env, "paused"),
+        ().into_val(&test.env),
+    );
+    assert!(paused);
+    assert!(pt_client.try_transfer(&test.user1, &test.user2, &1).is_err());
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "unpause_principal_token"),
+        ().into_val(&test.env),
+    );
+    pt_client.transfer(&test.user1, &test.user2, &1);
+    assert_eq!(test.get_pt_balance(&test.user2), 1);
+}
+
+#[test]
+fn test_pause_principal_token_requires_admin() {
+    let test = YieldManagerTest::setup();
+    let outsider = Address::generate(&test.env);
+
+    test.env.mock_auths(&[MockAuth {
+        address: &outsider,
+        invoke: &MockAuthInvoke {
+            contract: &test.yield_manager,
+            fn_name: "pause_principal_token",
+            args: ().into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = test.env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pause_principal_token"),
+        ().into_val(&test.env),
+    );
+    assert!(result.is_err());
+
+    let paused: bool = test.env.invoke_contract(
+        &test.pt,
+        &Symbol::new(&test.env, "paused"),
+        ().into_val(&test.env),
+    );
+    assert!(!paused);
+}
+
+#[test]
+fn test_admin_sets_principal_token_metadata_through_manager() {
+    let test = YieldManagerTest::setup();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_principal_token_metadata"),
+        (
+            String::from_str(&test.env, "PT Renamed"),
+            String::from_str(&test.env, "PTR"),
+        )
+            .into_val(&test.env),
+    );
+
+    let pt_client = TokenClient::new(&test.env, &test.pt);
+    assert_eq!(pt_client.name(), String::from_str(&test.env, "PT Renamed"));
+    assert_eq!(pt_client.symbol(), String::from_str(&test.env, "PTR"));
+    assert_eq!(pt_client.decimals(), TOKEN_DECIMALS);
+}
+
+#[test]
+fn test_set_principal_token_metadata_requires_admin() {
+    let test = YieldManagerTest::setup();
+    let outsider = Address::generate(&test.env);
+    let name = String::from_str(&test.env, "PT Renamed");
+    let symbol = String::from_str(&test.env, "PTR");
+
+    test.env.mock_auths(&[MockAuth {
+        address: &outsider,
+        invoke: &MockAuthInvoke {
+            contract: &test.yield_manager,
+            fn_name: "set_principal_token_metadata",
+            args: (&name, &symbol).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = test.env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_principal_token_metadata"),
+        (&name, &symbol).into_val(&test.env),
+    );
+    assert!(result.is_err());
+    assert_eq!(TokenClient::new(&test.env, &test.pt).symbol(), String::from_str(&test.env, "PT"));
+}
+
+#[test]
+fn test_distribute_yield_takes_protocol_fee() {
+    let test = YieldManagerTest::setup();
+    let fee_recipient = Address::generate(&test.env);
+
+    // 10% fee on distributed yield
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_fee"),
+        (1_000u32, &fee_recipient).into_val(&test.env),
+    );
+    let fee_bps: u32 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_fee_bps"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(fee_bps, 1_000);
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    let mut total_claimed = 0i128;
+    let mut total_fee = 0i128;
+    for _ in 0..2 {
+        test.advance_time(100);
+        test.simulate_yield(deposit_amount / 10);
+
+        let claimed: i128 = test.env.invoke_contract(
+            &test.yt,
+            &Symbol::new(&test.env, "claim_yield"),
+            (&test.user1,).into_val(&test.env),
+        );
+        assert!(claimed > 0);
+        total_claimed += claimed;
+        total_fee += claimed * 1_000 / 10_000;
+
+        // User receives the net amount, the recipient accrues the fee
+        assert_eq!(test.vault_balance(&test.user1), total_claimed - total_fee);
+        assert_eq!(test.vault_balance(&fee_recipient), total_fee);
+    }
+    assert!(total_fee > 0);
+}
+
+#[test]
+fn test_distribute_yield_zero_fee_pays_full_amount() {
+    let test = YieldManagerTest::setup();
+    let fee_recipient = Address::generate(&test.env);
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_fee"),
+        (0u32, &fee_recipient).into_val(&test.env),
+    );
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+    let claimed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claim_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+
+    assert_eq!(test.vault_balance(&test.user1), claimed);
+    assert_eq!(test.vault_balance(&fee_recipient), 0);
+}
+
+#[test]
+fn test_distribute_yield_capped_at_manager_balance() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    let (pt_minted, _) = test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(200);
+    test.simulate_yield(deposit_amount / 5);
+    test.get_exchange_rate();
+    test.advance_time(1000);
+
+    // All PT redeems first, then half of what's left for the YT leaves the manager
+    test.env.invoke_contract::<i128>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_minted, 0i128).into_val(&test.env),
+    );
+    let vault = TokenClient::new(&test.env, &test.vault_addr);
+    let sink = Address::generate(&test.env);
+    vault.transfer(&test.yield_manager, &sink, &(test.vault_balance(&test.yield_manager) / 2));
+
+    // The manager now holds less than the accrued yield
+    let held = test.vault_balance(&test.yield_manager);
+    let accrued: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claimable_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!(accrued > held);
+
+    // The claim pays out what is available instead of reverting
+    let user_before = test.vault_balance(&test.user1);
+    let claimed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claim_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(claimed, held);
+    assert_eq!(test.vault_balance(&test.user1), user_before + held);
+    assert_eq!(test.vault_balance(&test.yield_manager), 0);
+
+    // Only the paid portion was cleared
+    let remaining: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claimable_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(remaining, accrued - held);
+
+    // With nothing left to pay, a further claim returns zero
+    let claimed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claim_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(claimed, 0);
+}
+
+#[test]
+fn test_compound_redeposits_accrued_yield() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+
+    let claimable: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claimable_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!(claimable > 0);
+    let rate: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "peek_exchange_rate"),
+        ().into_val(&test.env),
+    );
+
+    let pt_before = test.get_pt_balance(&test.user1);
+    let yt_before = test.get_yt_balance(&test.user1);
+    let held_before = test.vault_balance(&test.yield_manager);
+
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "compound"),
+        (&test.user1,).into_val(&test.env),
+    );
+
+    // The claimed shares are re-deposited at the current rate
+    assert_eq!(pt_minted, claimable * rate);
+    assert_eq!(yt_minted, claimable * rate);
+    assert_eq!(test.get_pt_balance(&test.user1), pt_before + pt_minted);
+    assert_eq!(test.get_yt_balance(&test.user1), yt_before + yt_minted);
+
+    // No loose shares reach the user, they stay in the manager as backing
+    assert_eq!(test.vault_balance(&test.user1), 0);
+    assert_eq!(test.vault_balance(&test.yield_manager), held_before);
+    let accrued: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "accrued_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(accrued, 0);
+
+    // Nothing left to compound
+    let again: (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "compound"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(again, (0, 0));
+}
+
+#[test]
+#[should_panic(expected = "Maturity reached")]
+fn test_compound_after_maturity() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+    test.advance_time(1000);
+
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "compound"),
+        (&test.user1,).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_compound_counts_against_per_user_cap() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_per_user_cap"),
+        (shares,).into_val(&test.env),
+    );
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(100);
+    test.simulate_yield(deposit_amount / 10);
+
+    // The user is at the cap, so compounding more principal reverts
+    let result = test.env.try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "compound"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!(result.is_err());
+
+    // With room under a raised cap the compounded shares are tallied
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_per_user_cap"),
+        (shares * 2,).into_val(&test.env),
+    );
+    let (pt_minted, _): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "compound"),
+        (&test.user1,).into_val(&test.env),
+    );
+    let rate = test.get_exchange_rate();
+    let deposited: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_user_deposited"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert_eq!(deposited, shares + pt_minted / rate);
+}
+
+#[test]
+fn test_extend_maturity_before_maturity() {
+    let test = YieldManagerTest::setup();
+    let new_maturity = test.maturity + 500;
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "extend_maturity"),
+        (new_maturity,).into_val(&test.env),
+    );
+    let maturity: u64 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_maturity"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(maturity, new_maturity);
+
+    // The old maturity no longer locks the rate
+    test.advance_time(1100);
+    test.get_exchange_rate();
+    let summary: Summary = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_summary"),
+        ().into_val(&test.env),
+    );
+    assert!(!summary.rate_locked);
+}
+
+#[test]
+#[should_panic(expected = "New maturity must be later than current maturity")]
+fn test_extend_maturity_rejects_earlier_maturity() {
+    let test = YieldManagerTest::setup();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "extend_maturity"),
+        (test.maturity,).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Rate already locked")]
+fn test_extend_maturity_after_rate_locked() {
+    let test = YieldManagerTest::setup();
+
+    test.advance_time(1100);
+    test.get_exchange_rate();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "extend_maturity"),
+        (test.maturity + 500,).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Fee exceeds maximum")]
+fn test_set_fee_above_maximum() {
+    let test = YieldManagerTest::setup();
+    let fee_recipient = Address::generate(&test.env);
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "set_fee"),
+        (10_001u32, &fee_recipient).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_admin_transfer_two_step() {
+    let test = YieldManagerTest::setup();
+    let new_admin = Address::generate(&test.env);
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "propose_admin"),
+        (&new_admin,).into_val(&test.env),
+    );
+
+    // Proposing alone does not hand over control
+    let admin: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_admin"),
+        ().into_val(&test.env),
+    );
+    let pending_admin: Option<Address> = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_pending_admin"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(admin, test.admin);
+    assert_eq!(pending_admin, Some(new_admin.clone()));
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "accept_admin"),
+        ().into_val(&test.env),
+    );
+
+    let admin: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_admin"),
+        ().into_val(&test.env),
+    );
+    let pending_admin: Option<Address> = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_pending_admin"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(admin, new_admin);
+    assert_eq!(pending_admin, None);
+}
+
+#[test]
+fn test_accept_admin_rejects_non_pending_address() {
+    let test = YieldManagerTest::setup();
+    let new_admin = Address::generate(&test.env);
+    let outsider = Address::generate(&test.env);
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "propose_admin"),
+        (&new_admin,).into_val(&test.env),
+    );
+
+    // Only the outsider signs, so the pending admin's auth check fails
+    test.env.mock_auths(&[MockAuth {
+        address: &outsider,
+        invoke: &MockAuthInvoke {
+            contract: &test.yield_manager,
+            fn_name: "accept_admin",
+            args: ().into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = test.env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "accept_admin"),
+        ().into_val(&test.env),
+    );
+    assert!(result.is_err());
+
+    let admin: Address = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_admin"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(admin, test.admin);
+}
+
+#[test]
+#[should_panic(expected = "No pending admin")]
+fn test_accept_admin_without_proposal() {
+    let test = YieldManagerTest::setup();
+
+    test.env.invoke_contract::<()>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "accept_admin"),
+        ().into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_set_token_contracts_after_admin_rotation() {
+    let test = YieldManagerTest::setup();
+    let old_admin = test.admin.clone();
+    let new_admin = Address::generate(&test.env);
+
+    // Fresh manager whose token contracts are not wired yet
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&old_admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "propose_admin"),
+        (&new_admin,).into_val(&test.env),
+    );
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "accept_admin"),
+        ().into_val(&test.env),
+    );
+
+    // The old admin can no longer wire tokens
+    test.env.mock_auths(&[MockAuth {
+        address: &old_admin,
+        invoke: &MockAuthInvoke {
+            contract: &yield_manager,
+            fn_name: "set_token_contracts",
+            args: (&test.pt, &test.yt).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    let result = test.env.try_invoke_contract::<(), soroban_sdk::Error>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&test.pt, &test.yt).into_val(&test.env),
+    );
+    assert!(result.is_err());
+
+    // The new admin can
+    test.env.mock_auths(&[MockAuth {
+        address: &new_admin,
+        invoke: &MockAuthInvoke {
+            contract: &yield_manager,
+            fn_name: "set_token_contracts",
+            args: (&test.pt, &test.yt).into_val(&test.env),
+            sub_invokes: &[],
+        },
+    }]);
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&test.pt, &test.yt).into_val(&test.env),
+    );
+    let pt: Address = test.env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&test.env, "get_principal_token"),
+        ().into_val(&test.env),
+    );
+    assert_eq!(pt, test.pt);
+}
+
+#[test]
+#[should_panic(expected = "PT and YT must be different contracts")]
+fn test_set_token_contracts_rejects_identical_pt_and_yt() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    let pt = test.env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&test.env, "Principal Token"),
+            String::from_str(&test.env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&pt, &pt).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid token contract")]
+fn test_set_token_contracts_rejects_vault_as_token() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    let yt = test.env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(&test.env, "Yield Token"),
+            String::from_str(&test.env, "YT"),
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&test.vault_addr, &yt).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid token contract")]
+fn test_set_token_contracts_rejects_underlying_as_pt() {
+    let test = YieldManagerTest::setup();
+
+    // Fresh manager, so the underlying asset has not been cached yet
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    let yt = test.env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(&test.env, "Yield Token"),
+            String::from_str(&test.env, "YT"),
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&test.underlying_asset_addr, &yt).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid token contract")]
+fn test_set_token_contracts_rejects_underlying_as_yt() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    let pt = test.env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&test.env, "Principal Token"),
+            String::from_str(&test.env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&pt, &test.underlying_asset_addr).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "decimal/scale mismatch")]
+fn test_set_token_contracts_decimal_scale_mismatch() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+
+    // 6 decimal tokens match the 1e6 rate scale alone, but not the 7 decimal asset behind it
+    let pt = test.env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&test.env, "Principal Token"),
+            String::from_str(&test.env, "PT"),
+            6u32,
+            0i128,
+        ),
+    );
+    let yt = test.env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            6u32,
+            String::from_str(&test.env, "Yield Token"),
+            String::from_str(&test.env, "YT"),
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&pt, &yt).into_val(&test.env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "decimal/scale mismatch")]
+fn test_set_token_contracts_yt_decimals_mismatch() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+
+    // PT matches the asset and the 1e6 rate scale, the 7 decimal YT does not
+    let pt = test.env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&test.env, "Principal Token"),
+            String::from_str(&test.env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+    let yt = test.env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            7u32,
+            String::from_str(&test.env, "Yield Token"),
+            String::from_str(&test.env, "YT"),
+        ),
+    );
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&pt, &yt).into_val(&test.env),
+    );
+}
+
+#[test]
+fn test_token_decimals_stored_on_set_token_contracts() {
+    for rate_decimals in [6u32, 9u32] {
+        let test = YieldManagerTest::setup_with_rate_decimals(rate_decimals);
+        let stored: u32 = test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, "get_token_decimals"),
+            ().into_val(&test.env),
+        );
+        assert_eq!(stored, UNDERLYING_DECIMALS + rate_decimals);
+    }
+}
+
+#[test]
+fn test_pt_underlying_value_after_deposit() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    let value: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pt_underlying_value"),
+        (&test.user1,).into_val(&test.env),
+    );
+    assert!((deposit_amount - value).abs() <= 1);
+
+    // No PT, no value
+    let value: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "pt_underlying_value"),
+        (&test.user2,).into_val(&test.env),
+    );
+    assert_eq!(value, 0);
+}
+
+#[test]
+fn test_pt_and_yt_prices_track_exchange_rate() {
+    let test = YieldManagerTest::setup();
+    let price = |name: &str| -> i128 {
+        test.env.invoke_contract(
+            &test.yield_manager,
+            &Symbol::new(&test.env, name),
+            ().into_val(&test.env),
+        )
+    };
+
+    // Fresh manager: PT at par, no growth yet to project for YT
+    assert_eq!(price("pt_price"), 1_000_000);
+    assert_eq!(price("yt_price"), 0);
+
+    // 10% vault growth over the first 200s, projected over the 800s left: 40% more
+    // rate growth, per unit of the current 1.1 rate. PT stays at par
+    test.mint_underlying(&test.user1, 10_000_000);
+    let shares = test.vault_deposit(&test.user1, 10_000_000);
+    test.advance_time(200);
+    test.simulate_yield(1_000_000);
+    assert_eq!(price("pt_price"), 1_000_000);
+    assert_eq!(price("yt_price"), 100_000 * 800 * 1_000_000 / 200 / 1_100_000);
+
+    // Storing the rate, here through a deposit, doesn't move the price
+    test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+    assert_eq!(price("yt_price"), 100_000 * 800 * 1_000_000 / 200 / 1_100_000);
+
+    // With no further growth the yield ahead shrinks as maturity nears
+    test.advance_time(300);
+    assert_eq!(price("yt_price"), 100_000 * 500 * 1_000_000 / 500 / 1_100_000);
+
+    // After the rate locks PT captures further vault growth and YT is worthless
+    test.advance_time(500);
+    test.get_exchange_rate();
+    assert_eq!(price("yt_price"), 0);
+    assert_eq!(price("pt_price"), 1_000_000);
+    test.simulate_yield(1_100_000);
+    assert_eq!(price("yt_price"), 0);
+    assert_eq!(price("pt_price"), 1_100_000);
+}
+
+#[test]
+fn test_is_initialized_after_set_token_contracts() {
+    let test = YieldManagerTest::setup();
+
+    let yield_manager = test.env.register(
+        YieldManager,
+        (&test.admin, &test.vault_addr, VaultType::Vault4626, test.maturity, 1_000_000i128),
+    );
+    let initialized: bool = test.env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&test.env, "is_initialized"),
+        ().into_val(&test.env),
+    );
+    assert!(!initialized);
+
+    test.env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&test.env, "set_token_contracts"),
+        (&test.pt, &test.yt).into_val(&test.env),
+    );
+    let initialized: bool = test.env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&test.env, "is_initialized"),
+        ().into_val(&test.env),
+    );
+    assert!(initialized);
+}
+
+#[test]
+fn test_get_summary_matches_individual_getters() {
+    let test = YieldManagerTest::setup();
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    let (pt_minted, yt_minted): (i128, i128) = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    let summary: Summary = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_summary"),
+        ().into_val(&test.env),
+    );
+    let paused: bool = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "is_paused"),
+        ().into_val(&test.env),
+    );
+
+    assert_eq!(summary.maturity, test.maturity);
+    assert_eq!(summary.stored_exchange_rate, test.get_exchange_rate());
+    assert!(!summary.rate_locked);
+    assert_eq!(summary.paused, paused);
+    assert_eq!(summary.total_pt_supply, pt_minted);
+    assert_eq!(summary.total_yt_supply, yt_minted);
+    assert_eq!(summary.backing_shares, test.vault_balance(&test.yield_manager));
+    assert_eq!(summary.backing_shares, shares);
+
+    // After maturity the summary reports the locked rate
+    test.advance_time(1100);
+    test.get_exchange_rate();
+    let summary: Summary = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_summary"),
+        ().into_val(&test.env),
+    );
+    assert!(summary.rate_locked);
+}
+
+// Vault share token whose transfer re-enters the yield manager's deposit
+#[contract]
+struct ReentrantVault;
+
+#[contractimpl]
+impl ReentrantVault {
+    pub fn __constructor(env: Env, asset: Address) {
+        env.storage().instance().set(&symbol_short!("asset"), &asset);
+    }
+
+    pub fn query_asset(env: Env) -> Address {
+        env.storage().instance().get(&symbol_short!("asset")).unwrap()
+    }
+
+    pub fn set_target(env: Env, yield_manager: Address) {
+        env.storage().instance().set(&symbol_short!("target"), &yield_manager);
+    }
+
+    pub fn convert_to_assets(_env: Env, shares: i128) -> i128 {
+        shares
+    }
+
+    pub fn transfer(env: Env, from: Address, _to: Address, amount: i128) {
+        let yield_manager: Address = env.storage().instance().get(&symbol_short!("target")).unwrap();
+        env.invoke_contract::<(i128, i128)>(
+            &yield_manager,
+            &Symbol::new(&env, "deposit"),
+            (from, amount, 0i128, 0i128).into_val(&env),
+        );
+    }
+}
+
+#[test]
+fn test_deposit_reentry_from_vault_token_reverts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let maturity = env.ledger().timestamp() + 1000;
+
+    let underlying = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let vault_addr = env.register(ReentrantVault, (&underlying,));
+    let yield_manager = env.register(YieldManager, (&admin, &vault_addr, VaultType::Vault4626, maturity, 1_000_000i128));
+    let pt = env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&env, "Principal Token"),
+            String::from_str(&env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+    let yt = env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(&env, "Yield Token"),
+            String::from_str(&env, "YT"),
+        ),
+    );
+    env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&env, "set_token_contracts"),
+        (&pt, &yt).into_val(&env),
+    );
+    env.invoke_contract::<()>(
+        &vault_addr,
+        &Symbol::new(&env, "set_target"),
+        (&yield_manager,).into_val(&env),
+    );
+
+    let result = env.try_invoke_contract::<(i128, i128), soroban_sdk::Error>(
+        &yield_manager,
+        &Symbol::new(&env, "deposit"),
+        (&user, 1_000_000i128, 0i128, 0i128).into_val(&env),
+    );
+    // The host refuses to re-enter a contract already on the call stack
+    assert_eq!(
+        result,
+        Err(Ok(soroban_sdk::Error::from_type_and_code(
+            soroban_sdk::xdr::ScErrorType::Context,
+            soroban_sdk::xdr::ScErrorCode::InvalidAction,
+        )))
+    );
+
+    // Nothing was minted by either the outer or the re-entered deposit
+    assert_eq!(TokenClient::new(&env, &pt).balance(&user), 0);
+    assert_eq!(TokenClient::new(&env, &yt).balance(&user), 0);
+}
+
+// Deposits, accrues 20% yield, claims it and redeems half the PT after maturity, checking
+// the manager stays solvent. Returns (rate scale, PT minted, shares claimed as yield, shares redeemed for PT)
+fn run_scaled_lifecycle(rate_decimals: u32) -> (i128, i128, i128, i128) {
+    let test = YieldManagerTest::setup_with_rate_decimals(rate_decimals);
+
+    let deposit_amount = 10_000_000i128;
+    test.mint_underlying(&test.user1, deposit_amount);
+    let shares = test.vault_deposit(&test.user1, deposit_amount);
+    let (pt_minted, _) = test.env.invoke_contract::<(i128, i128)>(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "deposit"),
+        (&test.user1, shares, 0i128, 0i128).into_val(&test.env),
+    );
+
+    test.advance_time(200);
+    test.simulate_yield(deposit_amount / 5);
+
+    let claimed: i128 = test.env.invoke_contract(
+        &test.yt,
+        &Symbol::new(&test.env, "claim_yield"),
+        (&test.user1,).into_val(&test.env),
+    );
+
+    test.advance_time(1000);
+    let redeemed: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "redeem_principal"),
+        (&test.user1, pt_minted / 2, 0i128).into_val(&test.env),
+    );
+
+    // Yield paid plus all principal never exceeds the shares deposited, and the manager
+    // still holds what the remaining PT redeems for
+    let owed: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "preview_redeem"),
+        (test.get_pt_balance(&test.user1),).into_val(&test.env),
+    );
+    assert!(claimed + redeemed + owed <= shares);
+    assert!(test.vault_balance(&test.yield_manager) >= owed);
+
+    let rate_scale: i128 = test.env.invoke_contract(
+        &test.yield_manager,
+        &Symbol::new(&test.env, "get_rate_scale"),
+        ().into_val(&test.env),
+    );
+
+    (rate_scale, pt_minted, claimed, redeemed)
+}
+
+#[test]
+fn test_rate_scale_reconciles_across_precisions() {
+    let (scale6, pt6, claimed6, redeemed6) = run_scaled_lifecycle(6);
+    let (scale9, pt9, claimed9, redeemed9) = run_scaled_lifecycle(9);
+
+    assert_eq!(scale6, 1_000_000);
+    assert_eq!(scale9, 1_000_000_000);
+
+    // PT/YT amounts carry the extra precision, share amounts are unchanged
+    assert_eq!(pt9, pt6 * 1_000);
+    assert!(claimed6 > 0);
+    assert_eq!(claimed9, claimed6);
+    assert!(redeemed6 > 0);
+    assert_eq!(redeemed9, redeemed6);
+}
+
+#[test]
+#[should_panic(expected = "decimal/scale mismatch")]
+fn test_rate_scale_must_match_token_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let underlying = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let vault_addr = env.register(VAULT_WASM, (&underlying, 0u32));
+    let maturity = env.ledger().timestamp() + 1000;
+
+    // Tokens sized for a 1e6 rate scale against a 1e9 one
+    let yield_manager = env.register(
+        YieldManager,
+        (&admin, &vault_addr, VaultType::Vault4626, maturity, 1_000_000_000i128),
+    );
+    let pt = env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&env, "Principal Token"),
+            String::from_str(&env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+    let yt = env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(&env, "Yield Token"),
+            String::from_str(&env, "YT"),
+        ),
+    );
+    env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&env, "set_token_contracts"),
+        (&pt, &yt).into_val(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "decimal/scale mismatch")]
+fn test_rate_scale_must_be_power_of_ten() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let underlying = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let vault_addr = env.register(VAULT_WASM, (&underlying, 0u32));
+    let maturity = env.ledger().timestamp() + 1000;
+
+    // No token decimals line up with a rate scale that is not a power of ten
+    let yield_manager = env.register(
+        YieldManager,
+        (&admin, &vault_addr, VaultType::Vault4626, maturity, 2_000_000i128),
+    );
+    let pt = env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(&env, "Principal Token"),
+            String::from_str(&env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+    let yt = env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(&env, "Yield Token"),
+            String::from_str(&env, "YT"),
+        ),
+    );
+    env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(&env, "set_token_contracts"),
+        (&pt, &yt).into_val(&env),
+    );
+}
+
+// Deploys a manager with PT/YT at TOKEN_DECIMALS over `vault`, for either vault type
+fn register_manager(env: &Env, admin: &Address, vault: &Address, vault_type: VaultType, maturity: u64) -> (Address, Address, Address) {
+    let yield_manager = env.register(YieldManager, (admin, vault, vault_type, maturity, 1_000_000i128));
+    let pt = env.register(
+        PrincipalToken,
+        (
+            &yield_manager,
+            String::from_str(env, "Principal Token"),
+            String::from_str(env, "PT"),
+            TOKEN_DECIMALS,
+            0i128,
+        ),
+    );
+    let yt = env.register(
+        YieldToken,
+        (
+            &yield_manager,
+            TOKEN_DECIMALS,
+            String::from_str(env, "Yield Token"),
+            String::from_str(env, "YT"),
+        ),
+    );
+    env.invoke_contract::<()>(
+        &yield_manager,
+        &Symbol::new(env, "set_token_contracts"),
+        (&pt, &yt).into_val(env),
+    );
+
+    (yield_manager, pt, yt)
+}
+
+// Share token exposing both the 4626 and the Defindex rate entry points over the same books
+#[contract]
+struct MockShareVault;
+
+#[contractimpl]
+impl MockShareVault {
+    pub fn __constructor(env: Env, asset: Address) {
+        env.storage().instance().set(&symbol_short!("asset"), &asset);
+    }
+
+    pub fn query_asset(env: Env) -> Address {
+        env.storage().instance().get(&symbol_short!("asset")).unwrap()
+    }
+
+    pub fn get_assets(env: Env) -> soroban_sdk::Vec<AssetStrategySet> {
+        soroban_sdk::vec![
+            &env,
+            AssetStrategySet {
+                address: Self::query_asset(env.clone()),
+                strategies: soroban_sdk::Vec::new(&env),
+            }
+        ]
+    }
+
+    // Mints shares 1:1 against newly deposited assets
+    pub fn mint(env: Env, to: Address, shares: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(balance + shares));
+        Self::add_assets(env.clone(), shares);
+        let supply: i128 = env.storage().instance().get(&symbol_short!("supply")).unwrap_or(0);
+        env.storage().instance().set(&symbol_short!("supply"), &(supply + shares));
+    }
+
+    // Raises total assets without minting shares, i.e. yield
+    pub fn add_assets(env: Env, assets: i128) {
+        let total: i128 = env.storage().instance().get(&symbol_short!("assets")).unwrap_or(0);
+        env.storage().instance().set(&symbol_short!("assets"), &(total + assets));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage().persistent().set(&from, &(from_balance - amount));
+        let to_balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(to_balance + amount));
+    }
+
+    pub fn convert_to_assets(env: Env, shares: i128) -> i128 {
+        let assets: i128 = env.storage().instance().get(&symbol_short!("assets")).unwrap_or(0);
+        let supply: i128 = env.storage().instance().get(&symbol_short!("supply")).unwrap_or(0);
+        if supply == 0 {
+            return shares;
+        }
+        shares * assets / supply
+    }
+
+    pub fn get_asset_amounts_per_shares(env: Env, vault_shares: i128) -> soroban_sdk::Vec<i128> {
+        soroban_sdk::vec![&env, Self::convert_to_assets(env.clone(), vault_shares)]
+    }
+}
+
+// Deposits, accrues 10% yield and claims it through a manager of `vault_type`
+// Returns (initial rate, rate after yield, PT minted, shares claimed)
+fn run_mock_vault_cycle(vault_type: VaultType) -> (i128, i128, i128, i128) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let underlying = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let vault = env.register(MockShareVault, (&underlying,));
+    let maturity = env.ledger().timestamp() + 1000;
+
+    let shares = 10_000_000i128;
+    env.invoke_contract::<()>(&vault, &Symbol::new(&env, "mint"), (&user, shares).into_val(&env));
+
+    let (yield_manager, _, yt) = register_manager(&env, &admin, &vault, vault_type, maturity);
+    let initial_rate: i128 = env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&env, "get_exchange_rate"),
+        ().into_val(&env),
+    );
+
+    let (pt_minted, _) = env.invoke_contract::<(i128, i128)>(
+        &yield_manager,
+        &Symbol::new(&env, "deposit"),
+        (&user, shares, 0i128, 0i128).into_val(&env),
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    env.invoke_contract::<()>(&vault, &Symbol::new(&env, "add_assets"), (shares / 10,).into_val(&env));
+
+    let rate: i128 = env.invoke_contract(
+        &yield_manager,
+        &Symbol::new(&env, "get_exchange_rate"),
+        ().into_val(&env),
+    );
+    let claimed: i128 = env.invoke_contract(&yt, &Symbol::new(&env, "claim_yield"), (&user,).into_val(&env));
+    let user_shares: i128 = env.invoke_contract(&vault, &Symbol::new(&env, "balance"), (&user,).into_val(&env));
+    assert_eq!(user_shares, claimed);
+
+    (initial_rate, rate, pt_minted, claimed)
+}
+
+#[test]
+fn test_defindex_vault_matches_4626_path() {
+    let defindex = run_mock_vault_cycle(VaultType::VaultDefindex);
+    let vault_4626 = run_mock_vault_cycle(VaultType::Vault4626);
+
+    let (initial_rate, rate, pt_minted, claimed) = defindex;
+    assert_eq!(initial_rate, 1_000_000);
+    assert_eq!(rate, 1_100_000);
+    assert_eq!(pt_minted, 10_000_000 * 1_000_000);
+    assert!(claimed > 0);
+
+    // Same books read through either entry point give the same rate and yield
+    assert_eq!(defindex, vault_4626);
+}
@@ -1,6 +1,6 @@
 #![no_std]
 
-use soroban_sdk::{contractclient, contracttype, Address, Env};
+use soroban_sdk::{contractclient, contracttype, Address, Env, String};
 
 #[contracttype]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -9,6 +9,19 @@ pub enum VaultType {
     VaultDefindex = 1
 }
 
+/// Key scalar state of a yield manager, returned by `get_summary`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Summary {
+    pub maturity: u64,
+    pub stored_exchange_rate: i128,
+    pub rate_locked: bool,
+    pub paused: bool,
+    pub total_pt_supply: i128,
+    pub total_yt_supply: i128,
+    pub backing_shares: i128,
+}
+
 /// Trait defining the interface for the Yield Manager contract.
 /// This trait is used to generate the YieldManagerClient for type-safe cross-contract calls.
 #[contractclient(name = "YieldManagerClient")]
@@ -19,15 +32,68 @@ pub trait YieldManagerTrait {
         vault: Address,
         vault_type: VaultType,
         maturity: u64,
+        rate_scale: i128,
     );
 
     fn set_token_contracts(env: Env, pt_addr: Address, yt_addr: Address);
+    fn is_initialized(env: Env) -> bool;
+    fn propose_admin(env: Env, new_admin: Address);
+    fn accept_admin(env: Env);
+    fn get_admin(env: Env) -> Address;
+    fn get_pending_admin(env: Env) -> Option<Address>;
+    fn pause(env: Env);
+    fn unpause(env: Env);
+    fn is_paused(env: Env) -> bool;
+    fn pause_principal_token(env: Env);
+    fn unpause_principal_token(env: Env);
+    fn set_principal_token_metadata(env: Env, name: String, symbol: String);
+    fn set_fee(env: Env, fee_bps: u32, fee_recipient: Address);
+    fn get_fee_bps(env: Env) -> u32;
+    fn get_fee_recipient(env: Env) -> Option<Address>;
+    fn set_per_user_cap(env: Env, cap: i128);
+    fn get_per_user_cap(env: Env) -> i128;
+    fn get_user_deposited(env: Env, user: Address) -> i128;
     fn get_vault(env: Env) -> Address;
+    fn get_vault_type(env: Env) -> VaultType;
+    fn get_vault_info(env: Env) -> (Address, VaultType);
+    fn get_underlying_asset(env: Env) -> Address;
     fn get_principal_token(env: Env) -> Address;
     fn get_yield_token(env: Env) -> Address;
+    fn get_token_contracts(env: Env) -> (Address, Address);
     fn get_maturity(env: Env) -> u64;
+    fn is_matured(env: Env) -> bool;
+    fn is_rate_locked(env: Env) -> bool;
+    fn lock_rate(env: Env) -> i128;
+    fn extend_maturity(env: Env, new_maturity: u64);
+    fn get_rate_scale(env: Env) -> i128;
+    fn get_token_decimals(env: Env) -> u32;
+    fn total_principal_shares(env: Env) -> i128;
+    fn get_summary(env: Env) -> Summary;
     fn get_exchange_rate(env: Env) -> i128;
-    fn deposit(env: Env, from: Address, shares_amount: i128);
-    fn distribute_yield(env: Env, to: Address, shares_amount: i128);
-    fn redeem_principal(env: Env, from: Address, pt_amount: i128);
+    fn peek_exchange_rate(env: Env) -> i128;
+    fn preview_deposit(env: Env, shares_amount: i128) -> (i128, i128);
+    fn preview_redeem(env: Env, pt_amount: i128) -> i128;
+    fn pt_underlying_value(env: Env, user: Address) -> i128;
+    fn pt_price(env: Env) -> i128;
+    fn yt_price(env: Env) -> i128;
+    fn deposit(
+        env: Env,
+        from: Address,
+        shares_amount: i128,
+        min_pt_out: i128,
+        min_yt_out: i128,
+    ) -> (i128, i128);
+    fn deposit_underlying(
+        env: Env,
+        from: Address,
+        assets: i128,
+        min_pt_out: i128,
+        min_yt_out: i128,
+    ) -> (i128, i128);
+    fn distribute_yield(env: Env, to: Address, shares_amount: i128) -> i128;
+    fn compound(env: Env, user: Address) -> (i128, i128);
+    fn redeem_combined(env: Env, from: Address, amount: i128);
+    fn redeem_principal(env: Env, from: Address, pt_amount: i128, min_shares_out: i128) -> i128;
+    fn redeem_all(env: Env, from: Address) -> i128;
+    fn redeem_principal_to_underlying(env: Env, from: Address, pt_amount: i128) -> i128;
 }
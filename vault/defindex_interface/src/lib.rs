@@ -1,6 +1,23 @@
 #![no_std]
 
-use soroban_sdk::{contractclient, Address, Env, Vec};
+use soroban_sdk::{contractclient, contracttype, Address, Env, String, Vec};
+
+/// A strategy investing one of the vault's assets.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Strategy {
+    pub address: Address,
+    pub name: String,
+    pub paused: bool,
+}
+
+/// An asset managed by the vault together with its strategies.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetStrategySet {
+    pub address: Address,
+    pub strategies: Vec<Strategy>,
+}
 
 /// Trait defining the interface for the Defindex Vault contract.
 /// This trait is used to generate the contract client for type-safe cross-contract calls.
@@ -8,5 +25,6 @@ use soroban_sdk::{contractclient, Address, Env, Vec};
 pub trait DefindexVaultTrait {
     fn __constructor(e: Env, asset: Address, decimals_offset: u32, strategy: Address);
     fn get_asset_amounts_per_shares(e: Env, vault_shares: i128) -> Vec<i128>;
+    fn get_assets(e: Env) -> Vec<AssetStrategySet>;
 }
 
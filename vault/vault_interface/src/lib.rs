@@ -8,6 +8,7 @@ use soroban_sdk::{contractclient, Address, Env};
 pub trait VaultTrait {
     fn __constructor(e: Env, asset: Address, decimals_offset: u32, strategy: Address);
     fn convert_to_assets(e: &Env, shares: i128) -> i128;
+    fn query_asset(e: &Env) -> Address;
     fn deposit(
         e: &Env,
         assets: i128,
@@ -15,4 +16,11 @@ pub trait VaultTrait {
         from: Address,
         operator: Address,
     ) -> i128;
+    fn redeem(
+        e: &Env,
+        shares: i128,
+        receiver: Address,
+        owner: Address,
+        operator: Address,
+    ) -> i128;
 }